@@ -37,7 +37,7 @@ fn without(i: usize, numbers: &[i32]) -> impl Iterator<Item = &i32> + '_ {
 fn dampen<F>(f: F, numbers: &[i32]) -> bool 
 where F: Fn(i32) -> bool
 {
-    (0..numbers.len()).any(|i| diff(without(i, numbers)).all(|n| f(n)))
+    (0..numbers.len()).any(|i| diff(without(i, numbers)).all(&f))
 }
 
 pub fn part_two(input: &str) -> Option<usize> {
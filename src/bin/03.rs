@@ -1,3 +1,4 @@
+use advent_of_code::parse;
 use nom::IResult;
 use nom::bytes::complete::tag;
 use nom::character::complete as nom_chars;
@@ -24,11 +25,7 @@ fn parse_op(s: &str) -> IResult<&str, Op> {
     let (following, _) = tag("mul")(s)?;
     let (remain, (l, r)) = delimited(
         tag("("),
-        separated_pair(
-            nom_chars::i32,
-            tag(","),
-            nom_chars::i32
-        ),
+        separated_pair(parse::int, tag(","), parse::int),
         tag(")")
     )(following)?;
     Ok((remain, Op::Mul { l, r }))
@@ -44,18 +41,15 @@ fn parse_one(s: &str) -> IResult<&str, Option<Op>> {
 fn parse_all<F, R>(parser: F, s: &str) -> Vec<R>
   where F: Fn(&str) -> IResult<&str, Option<R>>
 {
-    let Ok((rest, ops)) = fold_many0(
+    let ops = fold_many0(
         parser,
         Vec::new,
         |mut acc, item| {
-            match item { Some(o) => acc.push(o), _ => {} };
+            if let Some(o) = item { acc.push(o) };
             acc
         }
-    )(s) else {
-        panic!("Failed to parse input");
-    };
-    assert_eq!(rest.len(), 0);
-    ops
+    );
+    parse::parse_all(ops, s).expect("Failed to parse input")
 }
 
 pub fn part_one(input: &str) -> Option<i32> {
@@ -1,7 +1,5 @@
-use itertools::{chain, Itertools};
 use std::cmp::max;
-use std::collections::HashSet;
-use advent_of_code::{Grid};
+use advent_of_code::{Grid, GridBounds, GridCells, GridIx, GridView, Pattern};
 
 advent_of_code::solution!(4);
 
@@ -16,43 +14,27 @@ fn count_word(word: &str, s: &str) -> usize
     find_word(word, s).count()
 }
 
+fn count_in_lines<I, L>(view: &GridView<Grid>, lines: I) -> usize
+where I: Iterator<Item=L>, L: Iterator<Item=GridIx>
+{
+    lines
+        .map(|line| count_word("XMAS", &view.cells(line).collect::<String>()))
+        .sum()
+}
+
 pub fn part_one(input: &str) -> Option<usize> {
     let grid = Grid::new(input);
-    let cols = (0..grid.cols).map(|c| grid.col(c));
-    let rows = (0..grid.rows).map(|r| grid.row(r));
-    let lr_diagonals_t = (0..grid.cols).map(|c| grid.lr_diagonal(0, c));
-    let lr_diagonals_l = (1..grid.rows).map(|r| grid.lr_diagonal(r, 0));
-    let rl_diagonals_t = (0..grid.cols).map(|c| grid.rl_diagonal(0, c));
-    let rl_diagonals_r = (1..grid.rows).map(|r| grid.rl_diagonal(r, grid.cols - 1));
-    
-    Some(chain![cols, rows, lr_diagonals_t, rl_diagonals_t, lr_diagonals_l, rl_diagonals_r]
-        .map(|s| {
-            //println!("'{s}'");
-            count_word("XMAS", &s) + count_word("SAMX", &s)
-        })
+    // rows and diagonals of each rotation cover all eight directions
+    Some(grid.view().rotations()
+        .iter()
+        .map(|v| count_in_lines(v, v.row_lines()) + count_in_lines(v, v.diagonal_lines()))
         .sum())
 }
 
 pub fn part_two(input: &str) -> Option<usize> {
     let grid = Grid::new(input);
-
-    let lr_diagonal_pos: HashSet<(usize, usize)> = chain![
-        (0..grid.cols).map(|c| (0, c)),
-         (1..grid.rows).map(|r| (r, 0)),
-    ].flat_map(|(r, c)| {
-        let diag = grid.lr_diagonal(r, c);
-        chain![find_word("MAS", &diag), find_word("SAM", &diag)].map(|i| (r + i + 1, c + i + 1)).collect_vec()
-    }).collect();
-
-    let rl_diagonal_pos: HashSet<(usize, usize)> = chain![
-        (0..grid.cols).map(|c| (0, c)),
-         (1..grid.rows).map(|r| (r, grid.cols - 1)),
-    ].flat_map(|(r, c)| {
-        let diag = grid.rl_diagonal(r, c);
-        chain![find_word("MAS", &diag), find_word("SAM", &diag)].map(|i| (r + i + 1, c - i - 1)).collect_vec()
-    }).collect();
-
-    Some(lr_diagonal_pos.intersection(&rl_diagonal_pos).count())
+    let x_mas = Pattern::new("M.S\n.A.\nM.S\n", '.');
+    Some(grid.find_pattern_any_orientation(&x_mas).len())
 }
 
 #[cfg(test)]
@@ -1,84 +1,42 @@
-use std::cmp::Ordering;
-use std::collections::{HashMap, HashSet};
-use itertools::Itertools;
+use advent_of_code::order::PartialOrder;
+use advent_of_code::parse::{comma_list, int, lines, parse_all, SectionError, Sections};
 use nom::bytes::complete::tag;
-use nom::character::complete as nom_chars;
-use nom::character::complete::newline;
-use nom::combinator::eof;
-use nom::multi::{separated_list1, many1};
 use nom::sequence::separated_pair;
 use nom::IResult;
 
 advent_of_code::solution!(5);
 
 fn parse_page_order(s: &str) -> IResult<&str, (u32, u32)> {
-    separated_pair(
-        nom_chars::u32,
-        tag("|"),
-        nom_chars::u32
-    )(s)
+    separated_pair(int, tag("|"), int)(s)
 }
 
 fn parse_pages(s: &str) -> IResult<&str, Vec<u32>> {
-    separated_list1(tag(","), nom_chars::u32)(s)
+    comma_list(int)(s)
 }
 
-fn parse(s: &str) -> IResult<&str, (Vec<(u32, u32)>, Vec<Vec<u32>>)> {
-    let (s, page_orders) = separated_list1(newline, parse_page_order)(s)?;
-    let (s, _) = many1(newline)(s)?;
-    let (s, pages) = separated_list1(newline, parse_pages)(s)?;
-    let (s, _) = many1(newline)(s)?;
-    eof(s)?;
-    Ok((s, (page_orders, pages)))
-}
+type PageOrders = Vec<(u32, u32)>;
 
-fn pre_post(page_orders: &[(u32, u32)]) -> HashMap<u32, (HashSet<u32>, HashSet<u32>)> {
-    page_orders.iter().fold(
-        HashMap::new(),
-        |m, &(l, r)| {
-            let mut m = m;
-            m.entry(l).or_insert((HashSet::new(), HashSet::new())).1.insert(r);
-            m.entry(r).or_insert((HashSet::new(), HashSet::new())).0.insert(l);
-            m
-    })
-}
-
-fn sort_pages(pages: &[u32], pre_post: &HashMap<u32, (HashSet<u32>, HashSet<u32>)>) -> Vec<u32> {
-    pages.iter().cloned().sorted_by(|l, r| {
-        if let Some((l_pre, l_post)) = pre_post.get(&l) {
-            if l_pre.contains(r) {
-                return Ordering::Greater;
-            }
-            if l_post.contains(r) {
-                return Ordering::Less;
-            }
-        }
-        return Ordering::Equal;
-    }).collect()
+fn parse(s: &str) -> Result<(PageOrders, Vec<Vec<u32>>), SectionError> {
+    let mut sections = Sections::new(s);
+    let page_orders = sections.parse("page ordering rules", |s| parse_all(lines(parse_page_order), s))?;
+    let pages = sections.parse("page updates", |s| parse_all(lines(parse_pages), s))?;
+    sections.finish()?;
+    Ok((page_orders, pages))
 }
 
 fn solve(input: &str) -> Option<(u32, u32)> {
-    let (_, (page_orders, pages_lists)) = parse(input)
+    let (page_orders, pages_lists) = parse(input)
         .expect("input must be fully parsed");
-    
-    let pages_pp = pre_post(&page_orders);
+
+    let order: PartialOrder<u32> = page_orders.into_iter().collect();
     let mut sum1 = 0;
     let mut sum2 = 0;
-    'page: for pages in pages_lists {
-        let mut pre: HashSet<u32> = HashSet::new();
-        let mut post: HashSet<u32> = HashSet::from_iter(pages.iter().skip(1).cloned());
-        assert_eq!(post.len() + 1, pages.len(), "repeated page updates break logic");
-        for page in &pages {
-            if let Some((l, r)) = pages_pp.get(page) {
-                if r.intersection(&pre).next().is_some() || l.intersection(&post).next().is_some() {
-                    sum2 += sort_pages(&pages, &pages_pp)[pages.len() / 2];
-                    continue 'page;
-                }
-            }
-            pre.insert(*page);
-            post.remove(page);
+    for pages in pages_lists {
+        if order.is_sorted(&pages) {
+            sum1 += pages[pages.len() / 2];
+        } else {
+            sum2 += order.sort(&pages).expect("consistent page order")[pages.len() / 2];
         }
-        sum1 += pages[pages.len() / 2]
     }
     Some((sum1, sum2))
 }
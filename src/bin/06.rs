@@ -1,70 +1,56 @@
-use advent_of_code;
-use advent_of_code::{Grid, GridIx};
+use advent_of_code::cycle::try_find_cycle;
+use advent_of_code::{Direction, GridDirSet, GridIx, GridSet, OwnedGrid};
 use itertools::Itertools;
-use std::collections::HashSet;
-use std::iter::from_fn;
 
 advent_of_code::solution!(6);
 
-fn find_start(grid: &Grid) -> GridIx {
-    (0..grid.rows)
-        .cartesian_product(0..grid.cols)
-        .find(|&(row, col)| '^' == grid.get(row, col))
-        .map(|(row, col)| GridIx {
-            row: row as isize,
-            col: col as isize,
-        })
-        .unwrap()
-}
-
-fn move_guard(grid: &Grid, pos: GridIx, dir: GridIx) -> Option<(GridIx, GridIx)> {
-    let next = pos.add(&dir);
-    let ahead = grid.try_get(next.row, next.col)?;
+fn move_guard(grid: &OwnedGrid<char>, pos: GridIx, dir: Direction) -> Option<(GridIx, Direction)> {
+    let next = pos + dir.delta();
+    let ahead = *grid.get(next)?;
     if ahead == '#' {
-        return Some((pos, dir.rot90()));
+        return Some((pos, dir.cw()));
     }
     Some((next, dir))
 }
 
-fn patrol(grid: &Grid, pos: GridIx) -> (bool, HashSet<GridIx>) {
+/// The cells visited by the guard before leaving the grid, or `None` if the guard walks in a loop.
+fn patrol(grid: &OwnedGrid<char>, pos: GridIx) -> Option<GridSet> {
+    let mut visited = GridSet::for_grid(grid);
+    let mut states = GridDirSet::for_grid(grid);
     let mut loc = pos;
-    let mut dir = GridIx { row: -1, col: 0 };
-    let mut pos_dir: HashSet<(GridIx, GridIx)> = HashSet::from([(loc, dir)]);
-    let mut cycle: bool = false;
-    let mut positions: HashSet<GridIx> = from_fn(|| {
-        (loc, dir) = move_guard(&grid, loc, dir)?;
-        if pos_dir.contains(&(loc, dir)) {
-            cycle = true;
+    let mut dir = Direction::N;
+    visited.insert(loc);
+    states.insert(loc, dir);
+    while let Some(next) = move_guard(grid, loc, dir) {
+        (loc, dir) = next;
+        if !states.insert(loc, dir) {
             return None;
         }
-        pos_dir.insert((loc, dir));
-        Some(loc)
-    })
-    .collect();
-    positions.insert(pos);
-    (cycle, positions)
+        visited.insert(loc);
+    }
+    Some(visited)
+}
+
+fn is_loop(grid: &OwnedGrid<char>, pos: GridIx) -> bool {
+    try_find_cycle((pos, Direction::N), |&(loc, dir)| move_guard(grid, loc, dir)).is_some()
 }
 
 pub fn part_one(input: &str) -> Option<usize> {
-    let grid = Grid::new(input);
-    let loc = find_start(&grid);
-    let (cycle, path) = patrol(&grid, loc);
-    assert_eq!(cycle, false);
-    Some(path.len())
+    let grid = OwnedGrid::parse(input, |c| c);
+    let loc = grid.find(&'^').unwrap();
+    Some(patrol(&grid, loc).expect("guard leaves").len())
 }
 
 pub fn part_two(input: &str) -> Option<usize> {
-    let grid = Grid::new(input);
-    let loc = find_start(&grid);
-    let (_cycle, path) = patrol(&grid, loc);
-    let mut input2 = String::from(input);
+    let mut grid = OwnedGrid::parse(input, |c| c);
+    let loc = grid.find(&'^').unwrap();
+    let path = patrol(&grid, loc).expect("guard leaves").iter().collect_vec();
     Some(path
         .into_iter()
-        .filter(|p| {
-            let raw_ix = grid.raw_index(p.row, p.col);
-            input2.replace_range(raw_ix..raw_ix+1, "#");
-            let (cycle, _pos) = patrol(&Grid {data: &input2, ..grid}, loc);
-            input2.replace_range(raw_ix..raw_ix+1, ".");
+        .filter(|&p| {
+            let original = grid.set(p, '#').unwrap();
+            let cycle = is_loop(&grid, loc);
+            grid.set(p, original);
             cycle
         })
         .count())
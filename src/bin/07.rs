@@ -1,5 +1,5 @@
+use advent_of_code::parse::{int, lines, parse_all};
 use nom::bytes::complete::tag;
-use nom::character::complete as nom_chars;
 use nom::multi::separated_list1;
 use nom::sequence::separated_pair;
 use nom::IResult;
@@ -23,22 +23,11 @@ impl Op {
 }
 
 fn parse_line(s: &str) -> IResult<&str, (i64, Vec<i64>)> {
-    separated_pair(
-        nom_chars::i64,
-        tag(": "),
-        separated_list1(tag(" "), nom_chars::i64),
-    )(s)
+    separated_pair(int, tag(": "), separated_list1(tag(" "), int))(s)
 }
 
 fn parse(s: &str) -> Vec<(i64, Vec<i64>)> {
-    s.lines()
-        .flat_map(parse_line)
-        .map(|(rest, b)| {
-            assert_eq!(rest.len(), 0);
-            //println!("{b:?}");
-            b
-        })
-        .collect()
+    parse_all(lines(parse_line), s).expect("equations")
 }
 
 fn solvable(ops: &[Op], result: i64, current: i64, numbers: &[i64]) -> bool {
@@ -48,14 +37,14 @@ fn solvable(ops: &[Op], result: i64, current: i64, numbers: &[i64]) -> bool {
     match numbers.first() {
         None => current == result,
         Some(&n) => ops
-            .into_iter()
+            .iter()
             .any(|op| solvable(ops, result, op.eval(current, n), &numbers[1..])),
     }
 }
 
 fn sum_solvable(ops: &[Op], equations: &[(i64, Vec<i64>)]) -> i64 {
     equations
-        .into_iter()
+        .iter()
         .filter(|(res, nums)| solvable(ops, *res, nums[0], &nums[1..]))
         .map(|(res, _nums)| res)
         .sum()
@@ -1,16 +1,13 @@
 use std::collections::HashSet;
-use itertools::{chain, iproduct, Itertools};
-use advent_of_code::{Grid, GridIx};
+use itertools::{chain, Itertools};
+use advent_of_code::{Grid, GridBounds, GridIx};
 
 advent_of_code::solution!(8);
 
 fn get_opposite_antinodes(grid: &Grid, a1: GridIx, a2: GridIx) -> Vec<GridIx> {
     // distance from A1 ---> A2
-    let delta = GridIx {
-        row: a2.row - a1.row,
-        col: a2.col - a1.col,
-    };
-    [a2.add(&delta), a1.add(&GridIx {row: -delta.row, col: -delta.col})]
+    let delta = a2 - a1;
+    [a2 + delta, a1 - delta]
         .into_iter()
         .filter(|ix| ix.is_within(grid))
         .collect()
@@ -19,17 +16,16 @@ fn get_opposite_antinodes(grid: &Grid, a1: GridIx, a2: GridIx) -> Vec<GridIx> {
 fn collect_antinodes<F>(grid: &Grid, get_antinodes: F) -> HashSet<GridIx>
   where F: Fn(&Grid, GridIx, GridIx) -> Vec<GridIx>
 {
-    let chars: HashSet<char> = HashSet::from_iter(grid.data.chars().filter(|c| c.is_alphanumeric()));
     //   for each character get all positions
-    chars
+    grid.positions_by_char()
      .into_iter()
-     .flat_map(|c| {
+     .filter(|(c, _)| c.is_alphanumeric())
+     .flat_map(|(_, positions)| {
          // for each pair get antinodes position
-         iproduct!(0..grid.rows, 0..grid.cols)
-             .filter(move |&(row, col)| c == grid.get(row, col))
-             .map(|(row, col)| GridIx::new_u(row, col) )
+         positions
+             .into_iter()
              .tuple_combinations()
-             .flat_map(|(a1, a2)| get_antinodes(&grid, a1, a2))
+             .flat_map(|(a1, a2)| get_antinodes(grid, a1, a2))
      })
      // get the size of the set of unique antinodes positions
      .collect::<HashSet<GridIx>>()
@@ -42,13 +38,10 @@ pub fn part_one(input: &str) -> Option<usize> {
 
 fn get_resonant_antinodes(grid: &Grid, a1: GridIx, a2: GridIx) -> Vec<GridIx> {
     // distance from A1 ---> A2
-    let d = GridIx {
-        row: a2.row - a1.row,
-        col: a2.col - a1.col,
-    };
+    let d = a2 - a1;
     chain![
-        (0..).map(|n| a2.add(&GridIx {row: n * d.row, col: n * d.col})).take_while(|&a| a.is_within(grid)),
-        (0..).map(|n| a1.add(&GridIx {row: -n * d.row, col: -n * d.col})).take_while(|&a| a.is_within(grid)),
+        grid.ray(a1, d),
+        grid.ray(a2, -d),
     ].collect()
 }
 
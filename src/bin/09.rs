@@ -2,6 +2,7 @@ use std::io::Write;
 use std::io::stdout;
 use itertools::Either::{Left, Right};
 use itertools::{repeat_n, Itertools};
+use advent_of_code::range_set::RangeSet;
 use crate::DiskPart::{File, Space};
 
 advent_of_code::solution!(9);
@@ -73,7 +74,7 @@ pub fn part_one(input: &str) -> Option<usize> {
     let (files, spaces) = parse(input);
     let compact = compact_disk(&files, &spaces);
     // debug_disk(&compact);
-    Some(checksum(compact.into_iter().map(|p| File(p))))
+    Some(checksum(compact.into_iter().map(File)))
 }
 
 enum DiskPart {
@@ -113,37 +114,44 @@ fn parse2(s: &str) -> Vec<DiskPart> {
     .collect()
 }
 
-fn compact_disk2(files: Vec<DiskPart>) -> Vec<DiskPart> {
-    let mut files = files;
-    // get the largest file index
-    // let last_file = files.iter().rev().find_map(|p| match p { File(i, _) => Some(*i), _ => None }).unwrap();
-    let last_file = files.len() / 2;
-
-    for original_id in (0..=last_file).rev() {
-        // println!("next: {original_id}");
-        let (current_pos, length) = files.iter()
-            .enumerate()
-            .rev()
-            .find_map(|(pos, p)| match p { File(p) => if p.id == original_id {Some((pos, p.length))} else {None}, _ => None })
-            .unwrap();
-        if let Some((space_pos, space)) = files
-            .iter()
-            .take(current_pos)
-            .enumerate()
-            .find_map(|(i, p)| match p { Space(s) => if *s >= length {Some((i, *s))} else { None }, _ => None })
-        {
-            // println!("move: file {original_id} length {length} from position {current_pos} to space at {space_pos} of size {space}");
-            files[current_pos] = Space(length);
-            if space == length {
-                files[space_pos] = new_file(original_id, length);
-            } else {
-                files[space_pos] = Space(space - length);
-                files.insert(space_pos, new_file(original_id, length));
+fn compact_disk2(disk: Vec<DiskPart>) -> Vec<DiskPart> {
+    let mut files = Vec::new();
+    let mut free = RangeSet::new();
+    let mut pos = 0;
+    for part in disk {
+        match part {
+            File(f) => {
+                let length = f.length as usize;
+                files.push((pos, f));
+                pos += length;
             }
-            // debug_disk(&files);
+            Space(s) => {
+                free.insert(pos..pos + s as usize);
+                pos += s as usize;
+            }
+        }
+    }
+
+    for (start, file) in files.iter_mut().rev() {
+        let length = file.length as usize;
+        if let Some(space) = free.first_fit(length, *start) {
+            free.remove(space.start..space.start + length);
+            free.insert(*start..*start + length);
+            *start = space.start;
+        }
+    }
+
+    files.sort_by_key(|(start, _)| *start);
+    let mut compact = Vec::new();
+    let mut pos = 0;
+    for (start, file) in files {
+        if start > pos {
+            compact.push(Space((start - pos) as u32));
         }
+        pos = start + file.length as usize;
+        compact.push(File(file));
     }
-    files
+    compact
 }
 
 pub fn part_two(input: &str) -> Option<usize> {
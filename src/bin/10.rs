@@ -1,19 +1,12 @@
-use std::collections::BTreeSet;
-use itertools::Itertools;
-use pathfinding::matrix::Matrix;
-use pathfinding::prelude::{bfs_reach, count_paths};
+use advent_of_code::paths::PathCounter;
+use advent_of_code::{GridBounds, GridIx, OwnedGrid};
 
 advent_of_code::solution!(10);
 
 const WALL: i8 = -111;
 
-fn parse(s: &str) -> Matrix<i8> {
-    Matrix::from_rows(s
-        .lines()
-        .map(|l| l
-            .chars()
-            .map(|c| c.to_digit(10).map_or(WALL, |n| n as i8)))
-    ).expect("grid")
+fn parse_grid(s: &str) -> OwnedGrid<i8> {
+    OwnedGrid::parse(s, |c| c.to_digit(10).map_or(WALL, |n| n as i8))
 }
 
 #[allow(dead_code)]
@@ -26,58 +19,34 @@ fn pretty_pos(i: i8) -> char {
 }
 
 #[allow(dead_code)]
-fn pretty(grid: &Matrix<i8>) -> String {
-    Itertools::intersperse(
-        grid.iter().map(|r| r.iter().cloned().map(pretty_pos).collect()),
-        String::from("\n")
-    ).collect()
+fn pretty(grid: &OwnedGrid<i8>) -> String {
+    grid.render_with(|&i| pretty_pos(i)).to_string()
 }
 
-fn is_nice(matrix: &Matrix<i8>, start: (usize, usize), end: (usize, usize)) -> bool {
-    match (matrix.get(start), matrix.get(end)) {
-        (Some(&s), Some(&e)) => s + 1 == e,
-        _ => false,
-    }
-}
-
-fn trailhead_score(matrix: &Matrix<i8>, start: (usize, usize), ends: &BTreeSet<(usize,usize)>) -> usize {
-    let reachable: BTreeSet<(usize,usize)> = bfs_reach(start, |&n1| {
-        matrix.neighbours(n1, false)
-            .filter(|&n2| is_nice(matrix, n1, n2))
-            .collect_vec()
-    }).collect();
-    reachable.intersection(ends).count()
+fn trailhead_score(grid: &OwnedGrid<i8>, ends: &[GridIx], start: GridIx) -> usize {
+    let distances = grid.bfs_distances(start, |from, to| grid[from] + 1 == grid[to]);
+    ends.iter()
+        .filter(|&&end| distances[end].is_some())
+        .count()
 }
 
 pub fn part_one(input: &str) -> Option<usize> {
-    let matrix = parse(input);
-    //println!("{}", pretty(&matrix));
-    let starts = matrix.keys().filter(|&p| matrix[p] == 0).collect_vec();
-    //println!("starts: {:?}", starts);
-    let ends: BTreeSet<(usize,usize)> = matrix.keys().filter(|&p| matrix[p] == 9).collect();
-    //println!("ends: {:?}", ends);
-    Some(starts.into_iter()
-        .map(|s| trailhead_score(&matrix, s, &ends))
+    let grid = parse_grid(input);
+    //println!("{}", pretty(&grid));
+    let ends = grid.find_all(|&h| h == 9).collect::<Vec<_>>();
+    Some(grid.find_all(|&h| h == 0)
+        .map(|s| trailhead_score(&grid, &ends, s))
         .sum())
 }
 
-fn trailhead_rating(matrix: &Matrix<i8>, start: (usize, usize), ends: &BTreeSet<(usize,usize)>) -> usize {
-    count_paths(
-        start,
-        |&n1| {
-            matrix.neighbours(n1, false)
-                .filter(|&n2| is_nice(matrix, n1, n2))
-                .collect_vec()
-        },
-        |e| ends.contains(e))
-}
-
 pub fn part_two(input: &str) -> Option<usize> {
-    let matrix = parse(input);
-    let starts = matrix.keys().filter(|&p| matrix[p] == 0).collect_vec();
-    let ends: BTreeSet<(usize,usize)> = matrix.keys().filter(|&p| matrix[p] == 9).collect();
-    Some(starts.into_iter()
-        .map(|s| trailhead_rating(&matrix, s, &ends))
+    let grid = parse_grid(input);
+    let mut ratings = PathCounter::new(
+        |&from: &GridIx| grid.neighbours4(from).filter(|&to| grid[from] + 1 == grid[to]).collect::<Vec<_>>(),
+        |&end: &GridIx| grid[end] == 9,
+    );
+    Some(grid.find_all(|&h| h == 0)
+        .map(|s| ratings.count(&s))
         .sum())
 }
 
@@ -1,5 +1,4 @@
-use std::collections::HashMap;
-use itertools::Itertools;
+use advent_of_code::memo::{Context, Memoised};
 use num_bigint::BigUint;
 use num_traits::One;
 
@@ -24,7 +23,7 @@ where U: Into<BigUint>
     } else {
         let digits = log10(&n) + 1;
         let half = &BigUint::from(10u32).pow(digits / 2);
-        if digits % 2 == 0 {
+        if digits.is_multiple_of(2) {
             (&n / half, Some(&n % half))
         } else {
             (n * 2024u32, None)
@@ -32,46 +31,33 @@ where U: Into<BigUint>
     }
 }
 
-struct CachedGenerations {
-    generations: HashMap<BigUint, Vec<BigUint>>
-}
+type Stones<'a> = Context<'a, (BigUint, usize), BigUint>;
 
-impl CachedGenerations {
-    fn new() -> Self {
-        Self { generations: HashMap::new() }
+/// Number of stones a stone turns into after `blinks`.
+fn blink(stones: &mut Stones, (n, blinks): (BigUint, usize)) -> BigUint {
+    if blinks == 0 {
+        return BigUint::one();
     }
+    let (n1, on2) = stone_change(n);
+    let g1 = stones.get((n1, blinks - 1));
+    let g2 = on2.map_or(BigUint::ZERO, |n2| stones.get((n2, blinks - 1)));
+    g1 + g2
+}
 
-    fn get<U>(&mut self, n: U, blinks: usize) -> BigUint
-    where U: Into<BigUint>
-    {
-        let n: BigUint = n.into();
-        if blinks == 0 {
-            return BigUint::one();
-        }
-        if !self.generations.contains_key(&n) {
-            self.generations.insert(n.clone(), Vec::new());
-        }
-        let v_len = self.generations[&n].len();
-        for blinks in v_len+1..blinks+1 {
-            let (n1, on2) = stone_change(n.clone());
-            let g1 = self.get(n1, blinks - 1);
-            let g2 = on2.map_or(BigUint::ZERO, |n2| self.get(n2, blinks - 1));
-            self.generations.get_mut(&n).unwrap().push(g1 + g2);
-        }
-        self.generations[&n][blinks - 1].clone()
-    }
+fn count_stones(input: &str, blinks: usize) -> BigUint {
+    let mut stones = Memoised::new(blink);
+    input.split_ascii_whitespace()
+        .flat_map(|s| s.parse::<BigUint>())
+        .map(|n| stones.get((n, blinks)))
+        .sum()
 }
 
 pub fn part_one(input: &str) -> Option<BigUint> {
-    let numbers = input.split_ascii_whitespace().flat_map(|s| s.parse::<BigUint>()).collect_vec();
-    let mut cache = CachedGenerations::new();
-    Some(numbers.into_iter().map(|n| cache.get(n, 25)).sum())
+    Some(count_stones(input, 25))
 }
 
 pub fn part_two(input: &str) -> Option<BigUint> {
-    let numbers = input.split_ascii_whitespace().flat_map(|s| s.parse::<BigUint>()).collect_vec();
-    let mut cache = CachedGenerations::new();
-    Some(numbers.into_iter().map(|n| cache.get(n, 75)).sum())
+    Some(count_stones(input, 75))
 }
 
 #[cfg(test)]
@@ -89,20 +75,19 @@ mod tests {
 
     #[test]
     fn test_cache() {
-        let mut cache = CachedGenerations::new();
-        assert_eq!(cache.get(125u32, 1), 1u32.into());
-        assert_eq!(cache.get(125u32, 2), 2u32.into());
-        assert_eq!(cache.get(125u32, 3), 2u32.into());
-        println!("{:?}", cache.generations);
-        assert_eq!(cache.get(125u32, 6), 7u32.into());
-        println!("{:?}", cache.generations);
-        assert_eq!(cache.get(17u32, 1), 2u32.into()); // 1 7
-        assert_eq!(cache.get(17u32, 2), 2u32.into()); // 2024 14168
-        assert_eq!(cache.get(17u32, 3), 3u32.into()); // 20 24 28676032
-        assert_eq!(cache.get(17u32, 4), 6u32.into()); // 2 0 2 4 2867 6032
-        assert_eq!(cache.get(17u32, 5), 8u32.into()); // 4048 1 4048 8096 28 67 60 32
-        assert_eq!(cache.get(17u32, 6), 15u32.into()); // 40 48 2024 40 48 80 96 2 8 6 7 6 0 3 2
-        println!("{:?}", cache.generations);
+        let mut stones = Memoised::new(blink);
+        let mut get = |n: u32, blinks| stones.get((n.into(), blinks));
+        assert_eq!(get(125, 1), 1u32.into());
+        assert_eq!(get(125, 2), 2u32.into());
+        assert_eq!(get(125, 3), 2u32.into());
+        assert_eq!(get(125, 6), 7u32.into());
+        assert_eq!(get(17, 1), 2u32.into()); // 1 7
+        assert_eq!(get(17, 2), 2u32.into()); // 2024 14168
+        assert_eq!(get(17, 3), 3u32.into()); // 20 24 28676032
+        assert_eq!(get(17, 4), 6u32.into()); // 2 0 2 4 2867 6032
+        assert_eq!(get(17, 5), 8u32.into()); // 4048 1 4048 8096 28 67 60 32
+        assert_eq!(get(17, 6), 15u32.into()); // 40 48 2024 40 48 80 96 2 8 6 7 6 0 3 2
+        assert!(stones.cached() > 12);
     }
 
     #[test]
@@ -114,6 +99,6 @@ mod tests {
     #[test]
     fn test_part_two() {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
-        assert!(result.map_or(false, |r| r > 55312u32.into()));
+        assert!(result.is_some_and(|r| r > 55312u32.into()));
     }
 }
@@ -1,51 +1,32 @@
 use std::collections::HashMap;
 use std::io::{stdin, stdout, Write};
+use advent_of_code::{GridBounds, GridIx, OwnedGrid, Torus};
 use bitflags::bitflags;
+use advent_of_code::math::lcm;
+use advent_of_code::parse::int_array;
 use itertools::Itertools;
-use nom::bytes::complete::tag;
-use nom::character::complete as nom_c;
-use nom::character::complete::newline;
-use nom::multi::many0;
-use nom::sequence::{preceded, separated_pair, terminated};
-use nom::IResult;
 
 advent_of_code::solution!(14);
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
-struct V {
-    x: i32,
-    y: i32,
-}
-
 #[derive(Clone, Copy, Debug)]
 struct Robot {
-    position: V,
-    velocity: V,
-}
-
-fn parse_vector(input: &str) -> IResult<&str, V> {
-    let (input, (x, y)) = separated_pair(nom_c::i32, tag(","), nom_c::i32)(input)?;
-    Ok((input, V { x, y }))
+    position: GridIx,
+    velocity: GridIx,
 }
 
 // p=0,4 v=3,-3
-fn parse_robot(input: &str) -> IResult<&str, Robot> {
-    let (input, position) = preceded(tag("p="), parse_vector)(input)?;
-    let (input, velocity) = preceded(tag(" v="), parse_vector)(input)?;
-    Ok((input, Robot { position, velocity }))
-}
-
-fn parse(input: &str) -> IResult<&str, Vec<Robot>> {
-    many0(terminated(parse_robot, newline))(input)
+fn parse(input: &str) -> Vec<Robot> {
+    input.lines()
+        .map(|line| {
+            let [px, py, vx, vy] = int_array(line).expect("robot position and velocity");
+            Robot { position: GridIx::new(py, px), velocity: GridIx::new(vy, vx) }
+        })
+        .collect()
 }
 
-fn simulate(robot: Robot, space: V, seconds: i32) -> Robot {
-    
+fn simulate(robot: Robot, space: Torus, seconds: isize) -> Robot {
     Robot {
-        position: V {
-            x: (robot.position.x + robot.velocity.x * seconds).rem_euclid(space.x),
-            y: (robot.position.y + robot.velocity.y * seconds).rem_euclid(space.y),
-        },
+        position: space.wrapping_add(robot.position, robot.velocity * seconds),
         ..robot
     }
 }
@@ -57,39 +38,29 @@ bitflags! {
     }
 }
 
-fn debug_map(view: D, space: V, robot_map: HashMap<V, usize>) {
-    let mx = space.x / 2;
-    let my = space.y / 2;
+fn debug_map(view: D, space: Torus, robot_map: HashMap<GridIx, usize>) {
+    let mid = GridIx::new_u(space.rows / 2, space.cols / 2);
     let quadrants = view.contains(D::QUADS);
     let dots = view.contains(D::DOTS);
     
-    for y in 0..space.y {
-        if quadrants && y == my {
-            println!("{0:1$}", ' ', space.x as usize);
-            continue;
-        }
-        let line: String = (0..space.x)
-            .map(|x|
-                if quadrants && x == mx {
-                    String::from(' ')
-                } else {
-                    robot_map.get(&V{x, y}).map_or(
-                        String::from(if dots {'.'} else {'0'}),
-                        |n| n.to_string()
-                    )
-                })
-            .collect();
-        println!("{line}");
-    }
-}
-
-fn debug_lobby(view: D, space: V, robots: &[Robot]) {
+    let lobby = OwnedGrid::from_fn(space.rows, space.cols, |ix| {
+        robot_map.get(&ix).map_or(
+            if dots {'.'} else {'0'},
+            |&n| char::from_digit(n as u32, 10).unwrap_or('+')
+        )
+    });
+    let middle = space.row_lines()
+        .flatten()
+        .filter(|ix| quadrants && (ix.row == mid.row || ix.col == mid.col));
+    println!("{}", lobby.render().highlight(middle, ' '));
+}
+
+fn debug_lobby(view: D, space: Torus, robots: &[Robot]) {
     debug_map(view, space, robots.iter().map(|robot| robot.position).counts())
 }
 
-fn part_one_parametrised(input: &str, space: V, seconds: i32, debug: bool) -> Option<usize> {
-    let (rest, robots) = parse(input).unwrap();
-    assert_eq!(rest, "");
+fn part_one_parametrised(input: &str, space: Torus, seconds: isize, debug: bool) -> Option<usize> {
+    let robots = parse(input);
 
     if debug {
         println!("Initial lobby:");
@@ -106,25 +77,24 @@ fn part_one_parametrised(input: &str, space: V, seconds: i32, debug: bool) -> Op
         debug_lobby(D::DOTS | D::QUADS, space, &lobby);
     }
 
-    let mx = space.x / 2;
-    let my = space.y / 2;
+    let mid = GridIx::new_u(space.rows / 2, space.cols / 2);
     let mut quads = [[0; 3]; 3];
     for robot in lobby {
-        let xi = mx.cmp(&robot.position.x) as i8;
-        let yi = my.cmp(&robot.position.y) as i8;
+        let xi = mid.col.cmp(&robot.position.col) as i8;
+        let yi = mid.row.cmp(&robot.position.row) as i8;
         quads[(xi + 1) as usize][(yi + 1) as usize] += 1;
     }
     Some(quads[0][0] * quads[0][2] * quads[2][0] * quads[2][2])
 }
 
-const LOBBY: V = V {x: 101, y: 103};
+const LOBBY: Torus = Torus {rows: 103, cols: 101};
 
 pub fn part_one(input: &str) -> Option<usize> {
-    const SECONDS: i32 = 100;
+    const SECONDS: isize = 100;
     part_one_parametrised(input, LOBBY, SECONDS, false)
 }
 
-fn longest_sequence(input: &[i32]) -> usize {
+fn longest_sequence(input: &[isize]) -> usize {
     let mut max = 0;
     let mut seq = 1;
     for (&previous, &current) in input.iter().zip(input.iter().skip(1)) {
@@ -138,17 +108,17 @@ fn longest_sequence(input: &[i32]) -> usize {
     max.max(seq)
 }
 
-fn robots_in_line(robots: &[Robot], space: V, seconds: i32) -> (HashMap<V, usize>, usize) {
+fn robots_in_line(robots: &[Robot], space: Torus, seconds: isize) -> (HashMap<GridIx, usize>, usize) {
     let robot_map = robots
         .iter()
         .map(|r| simulate(*r, space, seconds).position)
         .counts();
 
     let lines = robot_map.iter()
-        .sorted_by_key(|(v, _)| v.y)
-        .chunk_by(|(v, _)| v.y)
+        .sorted_by_key(|(ix, _)| ix.row)
+        .chunk_by(|(ix, _)| ix.row)
         .into_iter()
-        .map(|(_, g)| g.map(|(v, _)| v.x).sorted().collect_vec())
+        .map(|(_, g)| g.map(|(ix, _)| ix.col).sorted().collect_vec())
         .collect_vec();
 
     (robot_map, lines.iter().map(|l| longest_sequence(l)).max().unwrap())
@@ -161,13 +131,14 @@ fn interact(query: String) -> bool {
     stdin().read_line(&mut line).is_ok() && line.to_lowercase().contains("y")
 }
 
-fn part_two_parametrised(input: &str, space: V, interactive: Option<usize>) -> Option<i32> {
-    let (rest, robots) = parse(input).unwrap();
-    assert_eq!(rest, "");
+fn part_two_parametrised(input: &str, space: Torus, interactive: Option<usize>) -> Option<isize> {
+    let robots = parse(input);
     let mut max_line = 0;
-    let mut result: Option<i32> = None;
+    let mut result: Option<isize> = None;
+    // every robot is back where it started after this many seconds
+    let period = lcm(space.rows as isize, space.cols as isize);
 
-    for i in 1..(space.x * space.y) {
+    for i in 1..period {
         if interactive.is_some() && i % 1000 == 0 {
             println!("== {i:6} ============================")
         }
@@ -190,7 +161,7 @@ fn part_two_parametrised(input: &str, space: V, interactive: Option<usize>) -> O
     result
 }
 
-pub fn part_two(input: &str) -> Option<i32> {
+pub fn part_two(input: &str) -> Option<isize> {
     part_two_parametrised(input, LOBBY, None) // set Some(6) to search interactively
 }
 
@@ -202,7 +173,7 @@ mod tests {
     fn test_part_one() {
         let result = part_one_parametrised(
             &advent_of_code::template::read_file("examples", DAY),
-            V {x: 11, y: 7}, 
+            Torus {rows: 7, cols: 11},
             100,
             true,
         );
@@ -211,14 +182,14 @@ mod tests {
 
     #[test]
     fn test_longest_sequence() {
-        assert_eq!(longest_sequence(&vec![1, 2, 4, 5, 6, 8, 9]), 3);
+        assert_eq!(longest_sequence(&[1, 2, 4, 5, 6, 8, 9]), 3);
     }
     
     #[test]
     fn test_part_two() {
         let result = part_two_parametrised(
             &advent_of_code::template::read_file("examples", DAY), 
-            V {x: 11, y: 7}, 
+            Torus {rows: 7, cols: 11},
             None);
         assert_eq!(result, Some(1));
     }
@@ -1,22 +1,25 @@
+use std::convert::Infallible;
+use advent_of_code::parse::Sections;
+use advent_of_code::Direction;
 use itertools::Itertools;
 use pathfinding::matrix::Matrix;
 use pathfinding::matrix::directions as directions;
 
 advent_of_code::solution!(15);
 
-/// region Parse input
+// region Parse input
 
 fn parse(input: &str) -> (Matrix<char>, &str) {
-    let (grid, movements) = input.split_once("\n\n").expect("two input parts");
-    let grid = Matrix::from_rows(
-        grid.lines().map(|l| l.chars())
-    ).expect("rectangle grid");
-    (grid, movements)
+    let mut sections = Sections::new(input);
+    let grid = sections.parse("warehouse", |s| Matrix::from_rows(s.lines().map(|l| l.chars())));
+    let movements = sections.parse("movements", Ok::<_, Infallible>);
+    sections.finish().expect("only warehouse and movements");
+    (grid.expect("rectangle grid"), movements.unwrap())
 }
 
-/// endregion
+// endregion
 
-/// region Part one
+// region Part one
 
 fn find_pos(grid: &Matrix<char>) -> (usize, usize) {
     grid.items().find(|(_, &c)| c == '@').unwrap().0
@@ -34,13 +37,8 @@ fn move_dir(grid: &Matrix<char>, start: (usize, usize), dir: (isize, isize)) ->
 }
 
 fn to_dir(c: char) -> Option<(isize, isize)> {
-    match c {
-        '^' => Some(directions::N),
-        '>' => Some(directions::E),
-        'v' => Some(directions::S),
-        '<' => Some(directions::W),
-        _ => None,
-    }
+    let d = Direction::try_from(c).ok()?.delta();
+    Some((d.row, d.col))
 }
 
 fn attempt_move(grid: &mut Matrix<char>, pos: (usize, usize), dir: char) -> Option<(usize, usize)> {
@@ -80,9 +78,9 @@ pub fn part_one(input: &str) -> Option<usize> {
     Some(gps(&grid))
 }
 
-/// endregion Part one
+// endregion Part one
 
-/// region Part two
+// region Part two
 
 fn expand(c: char) -> [char; 2] {
     match c {
@@ -159,7 +157,7 @@ pub fn part_two(input: &str) -> Option<usize> {
     Some(gps(&grid))
 }
 
-/// endregion Part two
+// endregion Part two
 
 #[cfg(test)]
 mod tests {
@@ -0,0 +1,136 @@
+//! Detecting when an iterated simulation starts repeating itself.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// The states from index `start` on repeat every `length` steps.
+#[derive(Eq, PartialEq, Debug, Clone, Copy)]
+pub struct Cycle {
+    pub start: usize,
+    pub length: usize,
+}
+
+impl Cycle {
+    /// Index of the earlier state equal to the state after `steps` steps.
+    pub fn reduce(&self, steps: usize) -> usize {
+        if steps < self.start {
+            steps
+        } else {
+            self.start + (steps - self.start) % self.length
+        }
+    }
+}
+
+/// Finds the cycle of a sequence that may end, when `step` returns `None`, using Brent's algorithm.
+///
+/// Only keeps two states in memory, but calls `step` a few times more than [`find_cycle_hashed`].
+pub fn try_find_cycle<T, F>(initial: T, mut step: F) -> Option<Cycle>
+where T: PartialEq + Clone, F: FnMut(&T) -> Option<T>
+{
+    // find the length by moving the tortoise to the hare at powers of two
+    let mut power = 1;
+    let mut length = 1;
+    let mut tortoise = initial.clone();
+    let mut hare = step(&initial)?;
+    while tortoise != hare {
+        if power == length {
+            tortoise = hare.clone();
+            power *= 2;
+            length = 0;
+        }
+        hare = step(&hare)?;
+        length += 1;
+    }
+
+    // walk two states `length` apart until they meet at the start of the cycle
+    let mut tortoise = initial.clone();
+    let mut hare = initial;
+    for _ in 0..length {
+        hare = step(&hare)?;
+    }
+    let mut start = 0;
+    while tortoise != hare {
+        tortoise = step(&tortoise)?;
+        hare = step(&hare)?;
+        start += 1;
+    }
+    Some(Cycle { start, length })
+}
+
+/// Finds the cycle of an infinite sequence of states, see [`try_find_cycle`].
+///
+/// Never returns if the states do not repeat.
+pub fn find_cycle<T, F>(initial: T, mut step: F) -> Cycle
+where T: PartialEq + Clone, F: FnMut(&T) -> T
+{
+    try_find_cycle(initial, |s| Some(step(s))).expect("infinite sequence")
+}
+
+/// Remembers every state until one repeats, returning them in order with the cycle.
+fn history<T, F>(initial: T, mut step: F, limit: usize) -> (Vec<T>, Option<Cycle>)
+where T: Hash + Eq + Clone, F: FnMut(&T) -> T
+{
+    let mut seen = HashMap::from([(initial.clone(), 0)]);
+    let mut states = vec![initial];
+    while states.len() <= limit {
+        let next = step(states.last().unwrap());
+        if let Some(&start) = seen.get(&next) {
+            let cycle = Cycle { start, length: states.len() - start };
+            return (states, Some(cycle));
+        }
+        seen.insert(next.clone(), states.len());
+        states.push(next);
+    }
+    (states, None)
+}
+
+/// Finds the cycle by remembering all states, which calls `step` only once per state.
+///
+/// Never returns if the states do not repeat.
+pub fn find_cycle_hashed<T, F>(initial: T, step: F) -> Cycle
+where T: Hash + Eq + Clone, F: FnMut(&T) -> T
+{
+    history(initial, step, usize::MAX).1.expect("infinite sequence")
+}
+
+/// The state after `steps` steps, skipping over whole cycles once the states start repeating.
+pub fn state_after<T, F>(initial: T, step: F, steps: usize) -> T
+where T: Hash + Eq + Clone, F: FnMut(&T) -> T
+{
+    let (mut states, cycle) = history(initial, step, steps);
+    match cycle {
+        Some(cycle) => states.swap_remove(cycle.reduce(steps)),
+        None => states.swap_remove(steps),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 0, 1, 2, 3, 4, 5, 6, 7, 3, 4, ...
+    fn rho(n: &u32) -> u32 {
+        if *n == 7 { 3 } else { n + 1 }
+    }
+
+    #[test]
+    fn test_find_cycle() {
+        let expected = Cycle { start: 3, length: 5 };
+        assert_eq!(find_cycle(0, rho), expected);
+        assert_eq!(find_cycle_hashed(0, rho), expected);
+        assert_eq!(find_cycle(5, rho), Cycle { start: 0, length: 5 });
+        assert_eq!(find_cycle(7u32, |n| 10 - n), Cycle { start: 0, length: 2 });
+        assert_eq!(find_cycle(1, |_| 1), Cycle { start: 0, length: 1 });
+        assert_eq!(try_find_cycle(0, |&n| (n < 10).then_some(n + 1)), None);
+        assert_eq!(try_find_cycle(0, |n| Some(rho(n))), Some(expected));
+    }
+
+    #[test]
+    fn test_state_after() {
+        assert_eq!(Cycle { start: 3, length: 5 }.reduce(1_000_000_000), 5);
+        assert_eq!(state_after(0, rho, 2), 2);
+        assert_eq!(state_after(0, rho, 8), 3);
+        assert_eq!(state_after(0, rho, 1_000_000_000), 5);
+        assert_eq!(state_after(0u64, |n| n + 1, 10), 10);
+    }
+}
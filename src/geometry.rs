@@ -0,0 +1,149 @@
+//! Geometry of lattice polygons and of shapes made from grid cells.
+
+use crate::math::gcd;
+use crate::GridIx;
+
+fn edges(vertices: &[GridIx]) -> impl Iterator<Item=(GridIx, GridIx)> + '_ {
+    vertices.iter().copied().zip(vertices.iter().copied().cycle().skip(1))
+}
+
+/// Twice the area of the polygon with the given vertices in order, using the shoelace formula.
+///
+/// The area of a polygon with integer vertices is a multiple of one half, so doubling keeps it exact.
+pub fn double_area(vertices: &[GridIx]) -> usize {
+    edges(vertices)
+        .map(|(a, b)| a.row * b.col - a.col * b.row)
+        .sum::<isize>()
+        .unsigned_abs()
+}
+
+/// Number of lattice points on the edges of the polygon.
+///
+/// For a polygon with only horizontal and vertical edges this is also its perimeter.
+pub fn boundary_points(vertices: &[GridIx]) -> usize {
+    edges(vertices)
+        .map(|(a, b)| gcd(a.row - b.row, a.col - b.col))
+        .sum::<isize>()
+        .unsigned_abs()
+}
+
+/// Number of lattice points strictly inside the polygon, using Pick's theorem.
+///
+/// A degenerate polygon without area, e.g. with all vertices on one line, has none.
+pub fn interior_points(vertices: &[GridIx]) -> usize {
+    // A = I + B / 2 - 1
+    (double_area(vertices) + 2).saturating_sub(boundary_points(vertices)) / 2
+}
+
+/// Number of lattice points inside or on the edges of the polygon.
+///
+/// This is the number of cells in a shape dug out along a path of cells through the vertices.
+pub fn enclosed_points(vertices: &[GridIx]) -> usize {
+    interior_points(vertices) + boundary_points(vertices)
+}
+
+/// Number of edges between the cell `ix` and cells outside the shape described by `inside`.
+pub fn cell_edges<F: Fn(GridIx) -> bool>(ix: GridIx, inside: F) -> usize {
+    ix.neighbours4().into_iter().filter(|&n| !inside(n)).count()
+}
+
+/// Counts the corners of the shape's outline touching the cell `ix`, which is inside the shape.
+///
+/// An outline has as many corners as straight sides, so summing over all cells gives the sides.
+pub fn cell_corners<F: Fn(GridIx) -> bool>(ix: GridIx, inside: F) -> usize {
+    let dirs = GridIx::DIRECTIONS_4;
+    (0..4)
+        .filter(|&i| {
+            let (a, b) = (dirs[i], dirs[(i + 1) % 4]);
+            match (inside(ix + a), inside(ix + b)) {
+                (false, false) => true,
+                (true, true) => !inside(ix + a + b),
+                _ => false,
+            }
+        })
+        .count()
+}
+
+/// Perimeter of the shape made of the given cells, including the outlines of holes.
+pub fn perimeter<I, F>(cells: I, inside: F) -> usize
+where I: IntoIterator<Item=GridIx>, F: Fn(GridIx) -> bool
+{
+    cells.into_iter().map(|ix| cell_edges(ix, &inside)).sum()
+}
+
+/// Number of straight sides of the shape made of the given cells, including the outlines of holes.
+pub fn sides<I, F>(cells: I, inside: F) -> usize
+where I: IntoIterator<Item=GridIx>, F: Fn(GridIx) -> bool
+{
+    cells.into_iter().map(|ix| cell_corners(ix, &inside)).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_pick() {
+        let rectangle = [GridIx::new(0, 0), GridIx::new(0, 4), GridIx::new(3, 4), GridIx::new(3, 0)];
+        assert_eq!(double_area(&rectangle), 24);
+        assert_eq!(boundary_points(&rectangle), 14);
+        assert_eq!(interior_points(&rectangle), 6);
+        assert_eq!(enclosed_points(&rectangle), 20);
+
+        let triangle = [GridIx::new(0, 0), GridIx::new(2, 3), GridIx::new(0, 3)];
+        assert_eq!(double_area(&triangle), 6);
+        assert_eq!(boundary_points(&triangle), 1 + 3 + 2);
+        assert_eq!(interior_points(&triangle), 1);
+
+        let segment = [GridIx::new(0, 0), GridIx::new(0, 4)];
+        assert_eq!(double_area(&segment), 0);
+        assert_eq!(interior_points(&segment), 0);
+        let collinear = [GridIx::new(0, 0), GridIx::new(2, 2), GridIx::new(4, 4)];
+        assert_eq!(interior_points(&collinear), 0);
+    }
+
+    #[test]
+    fn test_dug_outline() {
+        // R 2, D 2, L 1, D 1, L 1, U 3: the cells on and inside the path
+        let path = [GridIx::new(0, 0), GridIx::new(0, 2), GridIx::new(2, 2), GridIx::new(2, 1), GridIx::new(3, 1), GridIx::new(3, 0)];
+        assert_eq!(enclosed_points(&path), 11);
+    }
+
+    fn shape(cells: &[(isize, isize)]) -> HashSet<GridIx> {
+        cells.iter().map(|&(r, c)| GridIx::new(r, c)).collect()
+    }
+
+    fn perimeter_and_sides(cells: &HashSet<GridIx>) -> (usize, usize) {
+        let inside = |ix| cells.contains(&ix);
+        (perimeter(cells.iter().copied(), inside), sides(cells.iter().copied(), inside))
+    }
+
+    #[test]
+    fn test_cell_shapes() {
+        let single = shape(&[(0, 0)]);
+        assert_eq!(cell_corners(GridIx::new(0, 0), |ix| single.contains(&ix)), 4);
+        assert_eq!(perimeter_and_sides(&single), (4, 4));
+
+        // #.
+        // #.
+        // ##
+        let l_shape = shape(&[(0, 0), (1, 0), (2, 0), (2, 1)]);
+        let inside = |ix| l_shape.contains(&ix);
+        assert_eq!(cell_edges(GridIx::new(1, 0), inside), 2);
+        assert_eq!(cell_corners(GridIx::new(1, 0), inside), 0);
+        assert_eq!(cell_corners(GridIx::new(2, 0), inside), 2);
+        assert_eq!(perimeter_and_sides(&l_shape), (10, 6));
+
+        // ###
+        // #.#
+        // ###
+        let ring = shape(&[(0, 0), (0, 1), (0, 2), (1, 0), (1, 2), (2, 0), (2, 1), (2, 2)]);
+        assert_eq!(perimeter_and_sides(&ring), (16, 8));
+
+        // #.
+        // .#
+        let diagonal = shape(&[(0, 0), (1, 1)]);
+        assert_eq!(perimeter_and_sides(&diagonal), (8, 8));
+    }
+}
@@ -1,17 +1,63 @@
 pub mod template;
+pub mod cycle;
+pub mod geometry;
+pub mod linear;
+pub mod math;
+pub mod memo;
+pub mod order;
+pub mod parse;
+pub mod paths;
+pub mod range_set;
 
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::ops::{Add, AddAssign, Index, IndexMut, Mul, Neg, Sub, SubAssign};
+use paths::ShortestPaths;
 
 pub struct Grid<'a> {
     pub rows: usize,
     pub cols: usize,
-    pub data: &'a str
+    pub data: &'a str,
+    // bytes per line including the line ending
+    stride: usize,
 }
 
 impl<'a> Grid<'a> {
+    /// Reads the puzzle text, panicking if it is not a rectangular ASCII grid.
     pub fn new(data: &'a str) -> Self {
-        let rows = data.lines().count();
-        let cols = data.lines().map(|l| l.len()).max().unwrap_or(0);
-        Grid { rows, cols, data }
+        Grid::try_new(data).unwrap_or_else(|e| panic!("invalid grid: {e}"))
+    }
+
+    /// Reads the puzzle text, accepting `\n` or `\r\n` line endings and ignoring trailing empty lines.
+    ///
+    /// Every line must be ASCII, have the same length and use the same line ending.
+    pub fn try_new(data: &'a str) -> Result<Self, GridError> {
+        let body = data.trim_end_matches(['\r', '\n']);
+        if body.is_empty() {
+            return Ok(Grid { rows: 0, cols: 0, data, stride: 0 });
+        }
+        let lines = body.split('\n').collect::<Vec<_>>();
+        let crlf = lines[0].ends_with('\r');
+        let cols = lines[0].len() - crlf as usize;
+        for (i, line) in lines.iter().enumerate() {
+            let (content, cr) = match line.strip_suffix('\r') {
+                Some(content) => (content, true),
+                None => (*line, false),
+            };
+            let line_no = i + 1;
+            if cr != crlf && i + 1 < lines.len() {
+                return Err(GridError::MixedLineEndings { line: line_no });
+            }
+            if !content.is_ascii() {
+                return Err(GridError::NonAscii { line: line_no });
+            }
+            if content.len() != cols {
+                return Err(GridError::Ragged { line: line_no, len: content.len(), expected: cols });
+            }
+        }
+        let stride = cols + 1 + crlf as usize;
+        Ok(Grid { rows: lines.len(), cols, data, stride })
     }
 
     pub fn try_get(&self, row: isize, col: isize) -> Option<char> {
@@ -26,7 +72,34 @@ impl<'a> Grid<'a> {
     }
 
     pub fn raw_index(&self, row: isize, col: isize) -> usize {
-        row as usize * (self.cols + 1) + col as usize
+        row as usize * self.stride + col as usize
+    }
+
+    /// Iterates over all characters with their positions in row-major order.
+    pub fn iter(&self) -> impl Iterator<Item=(GridIx, char)> + '_ {
+        (0..self.rows)
+            .flat_map(move |row| (0..self.cols).map(move |col| (GridIx::new_u(row, col), self.get(row, col))))
+    }
+
+    /// Position of the first occurrence of `c` in row-major order.
+    pub fn find(&self, c: char) -> Option<GridIx> {
+        self.iter().find(|&(_, x)| x == c).map(|(ix, _)| ix)
+    }
+
+    /// Positions of all characters matching the predicate in row-major order.
+    pub fn find_all<'s, P>(&'s self, mut predicate: P) -> impl Iterator<Item=GridIx> + 's
+    where P: FnMut(char) -> bool + 's
+    {
+        self.iter().filter(move |&(_, c)| predicate(c)).map(|(ix, _)| ix)
+    }
+
+    /// Index from each character to all of its positions, built in a single pass.
+    pub fn positions_by_char(&self) -> HashMap<char, Vec<GridIx>> {
+        let mut positions: HashMap<char, Vec<GridIx>> = HashMap::new();
+        for (ix, c) in self.iter() {
+            positions.entry(c).or_default().push(ix);
+        }
+        positions
     }
 
     pub fn row(&self, row: usize) -> String {
@@ -59,7 +132,10 @@ impl<'a> Grid<'a> {
     }
 }
 
-#[derive(Eq, Hash, PartialEq, Debug, Clone, Copy)]
+/// Position on a grid, or a difference between two positions.
+///
+/// Ordering is row-major, i.e. by row first and then by column.
+#[derive(Eq, Hash, PartialEq, Debug, Clone, Copy, PartialOrd, Ord)]
 pub struct GridIx {
     pub row: isize,
     pub col: isize,
@@ -81,15 +157,1602 @@ impl GridIx {
         }
     }
 
-    pub fn add(&self, other: &GridIx) -> GridIx {
-        GridIx {
-            row: self.row + other.row,
-            col: self.col + other.col,
+    pub fn is_within<G: GridBounds + ?Sized>(&self, grid: &G) -> bool {
+        0 <= self.row && (self.row as usize) < grid.rows()
+            && 0 <= self.col && (self.col as usize) < grid.cols()
+    }
+
+    /// Orthogonal unit steps in clockwise order, starting up.
+    pub const DIRECTIONS_4: [GridIx; 4] = [
+        GridIx { row: -1, col: 0 },
+        GridIx { row: 0, col: 1 },
+        GridIx { row: 1, col: 0 },
+        GridIx { row: 0, col: -1 },
+    ];
+
+    /// Orthogonal and diagonal unit steps in clockwise order, starting up.
+    pub const DIRECTIONS_8: [GridIx; 8] = [
+        GridIx { row: -1, col: 0 },
+        GridIx { row: -1, col: 1 },
+        GridIx { row: 0, col: 1 },
+        GridIx { row: 1, col: 1 },
+        GridIx { row: 1, col: 0 },
+        GridIx { row: 1, col: -1 },
+        GridIx { row: 0, col: -1 },
+        GridIx { row: -1, col: -1 },
+    ];
+
+    /// Wraps into `0..rows` and `0..cols`, as if the grid repeated in every direction.
+    pub fn wrap(&self, rows: usize, cols: usize) -> GridIx {
+        GridIx::new(self.row.rem_euclid(rows as isize), self.col.rem_euclid(cols as isize))
+    }
+
+    /// Distance when moving only orthogonally.
+    pub fn manhattan(&self, other: &GridIx) -> usize {
+        self.row.abs_diff(other.row) + self.col.abs_diff(other.col)
+    }
+
+    /// Distance when also moving diagonally.
+    pub fn chebyshev(&self, other: &GridIx) -> usize {
+        self.row.abs_diff(other.row).max(self.col.abs_diff(other.col))
+    }
+
+    /// Sign of each coordinate, turning a difference into a single step towards it.
+    pub fn signum(&self) -> GridIx {
+        GridIx::new(self.row.signum(), self.col.signum())
+    }
+
+    /// The four orthogonal neighbours, regardless of any grid bounds.
+    pub fn neighbours4(&self) -> [GridIx; 4] {
+        GridIx::DIRECTIONS_4.map(|d| *self + d)
+    }
+
+    /// The eight surrounding neighbours, regardless of any grid bounds.
+    pub fn neighbours8(&self) -> [GridIx; 8] {
+        GridIx::DIRECTIONS_8.map(|d| *self + d)
+    }
+}
+
+impl Add for GridIx {
+    type Output = GridIx;
+
+    fn add(self, other: GridIx) -> GridIx {
+        GridIx::new(self.row + other.row, self.col + other.col)
+    }
+}
+
+impl Sub for GridIx {
+    type Output = GridIx;
+
+    fn sub(self, other: GridIx) -> GridIx {
+        GridIx::new(self.row - other.row, self.col - other.col)
+    }
+}
+
+impl Neg for GridIx {
+    type Output = GridIx;
+
+    fn neg(self) -> GridIx {
+        GridIx::new(-self.row, -self.col)
+    }
+}
+
+impl Mul<isize> for GridIx {
+    type Output = GridIx;
+
+    fn mul(self, n: isize) -> GridIx {
+        GridIx::new(n * self.row, n * self.col)
+    }
+}
+
+impl AddAssign for GridIx {
+    fn add_assign(&mut self, other: GridIx) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for GridIx {
+    fn sub_assign(&mut self, other: GridIx) {
+        *self = *self - other;
+    }
+}
+
+/// An error which can be returned when reading a [`Grid`] from puzzle text.
+///
+/// Line numbers start at 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GridError {
+    Ragged { line: usize, len: usize, expected: usize },
+    MixedLineEndings { line: usize },
+    NonAscii { line: usize },
+}
+
+impl Error for GridError {}
+
+impl Display for GridError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GridError::Ragged { line, len, expected } =>
+                write!(f, "line {line} has {len} characters, expecting {expected} like the first line"),
+            GridError::MixedLineEndings { line } =>
+                write!(f, "line {line} ends differently than the first line"),
+            GridError::NonAscii { line } =>
+                write!(f, "line {line} contains non-ASCII characters"),
+        }
+    }
+}
+
+/// Compass direction on a grid, where north is up (decreasing row).
+#[derive(Eq, Hash, PartialEq, Debug, Clone, Copy, PartialOrd, Ord)]
+pub enum Direction {
+    N,
+    NE,
+    E,
+    SE,
+    S,
+    SW,
+    W,
+    NW,
+}
+
+impl Direction {
+    /// All directions in clockwise order, starting north.
+    pub const ALL: [Direction; 8] = [
+        Direction::N,
+        Direction::NE,
+        Direction::E,
+        Direction::SE,
+        Direction::S,
+        Direction::SW,
+        Direction::W,
+        Direction::NW,
+    ];
+
+    /// Orthogonal directions in clockwise order, starting north.
+    pub const ORTHOGONAL: [Direction; 4] = [Direction::N, Direction::E, Direction::S, Direction::W];
+
+    /// Diagonal directions in clockwise order, starting north-east.
+    pub const DIAGONAL: [Direction; 4] = [Direction::NE, Direction::SE, Direction::SW, Direction::NW];
+
+    /// Iterates over all eight directions clockwise, starting north.
+    pub fn all() -> impl Iterator<Item=Direction> {
+        Direction::ALL.into_iter()
+    }
+
+    /// Unit step in this direction.
+    pub fn delta(self) -> GridIx {
+        GridIx::DIRECTIONS_8[self as usize]
+    }
+
+    pub fn is_diagonal(self) -> bool {
+        self as usize % 2 == 1
+    }
+
+    /// Rotates clockwise by the given number of 45° steps, negative counts rotate counter-clockwise.
+    pub fn rotate45(self, count: i32) -> Direction {
+        Direction::ALL[(self as i32 + count).rem_euclid(8) as usize]
+    }
+
+    /// Rotates clockwise by the given number of 90° turns, negative counts rotate counter-clockwise.
+    pub fn rotate90(self, count: i32) -> Direction {
+        self.rotate45(2 * count)
+    }
+
+    /// Turns 90° clockwise.
+    pub fn cw(self) -> Direction {
+        self.rotate90(1)
+    }
+
+    /// Turns 90° counter-clockwise.
+    pub fn ccw(self) -> Direction {
+        self.rotate90(-1)
+    }
+
+    pub fn opposite(self) -> Direction {
+        self.rotate90(2)
+    }
+}
+
+impl From<Direction> for GridIx {
+    fn from(dir: Direction) -> Self {
+        dir.delta()
+    }
+}
+
+/// Parses arrows `^>v<`, `UDLR` and compass letters `NESW`.
+impl TryFrom<char> for Direction {
+    type Error = DirectionFromCharError;
+
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        match c {
+            '^' | 'U' | 'N' => Ok(Direction::N),
+            '>' | 'R' | 'E' => Ok(Direction::E),
+            'v' | 'D' | 'S' => Ok(Direction::S),
+            '<' | 'L' | 'W' => Ok(Direction::W),
+            c => Err(DirectionFromCharError(c)),
+        }
+    }
+}
+
+/// An error which can be returned when parsing a [`Direction`] from a character.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DirectionFromCharError(pub char);
+
+impl Error for DirectionFromCharError {}
+
+impl Display for DirectionFromCharError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "expecting one of '^>v<', 'UDLR' or 'NESW', got {:?}", self.0)
+    }
+}
+
+/// Size of a rectangular grid, providing bounded iteration around positions.
+pub trait GridBounds {
+    fn rows(&self) -> usize;
+
+    fn cols(&self) -> usize;
+
+    /// Orthogonal neighbours of `ix` that lie within the grid.
+    fn neighbours4(&self, ix: GridIx) -> impl Iterator<Item=GridIx> + '_ {
+        ix.neighbours4().into_iter().filter(|n| n.is_within(self))
+    }
+
+    /// Orthogonal and diagonal neighbours of `ix` that lie within the grid.
+    fn neighbours8(&self, ix: GridIx) -> impl Iterator<Item=GridIx> + '_ {
+        ix.neighbours8().into_iter().filter(|n| n.is_within(self))
+    }
+
+    /// Positions reached by repeatedly stepping by `dir` from `start` (excluded)
+    /// until leaving the grid.
+    fn ray(&self, start: GridIx, dir: GridIx) -> impl Iterator<Item=GridIx> + '_ {
+        std::iter::successors(Some(start + dir), move |&ix| Some(ix + dir))
+            .take_while(|ix| ix.is_within(self))
+    }
+
+    /// Position `ix` moved back into the grid as if its edges were glued together (a torus).
+    fn wrap(&self, ix: GridIx) -> GridIx {
+        ix.wrap(self.rows(), self.cols())
+    }
+
+    /// Moves by `delta`, wrapping around the edges.
+    fn wrapping_add(&self, ix: GridIx, delta: GridIx) -> GridIx {
+        self.wrap(ix + delta)
+    }
+
+    /// Orthogonal neighbours of `ix`, wrapping around the edges.
+    fn wrapping_neighbours4(&self, ix: GridIx) -> [GridIx; 4] {
+        ix.neighbours4().map(|n| self.wrap(n))
+    }
+
+    /// Orthogonal and diagonal neighbours of `ix`, wrapping around the edges.
+    fn wrapping_neighbours8(&self, ix: GridIx) -> [GridIx; 8] {
+        ix.neighbours8().map(|n| self.wrap(n))
+    }
+
+    /// Positions from `start` (included) in steps of `dir` until leaving the grid.
+    fn line(&self, start: GridIx, dir: GridIx) -> impl Iterator<Item=GridIx> + '_ {
+        std::iter::once(start).filter(|ix| ix.is_within(self)).chain(self.ray(start, dir))
+    }
+
+    /// Each row from left to right, top to bottom.
+    fn row_lines(&self) -> impl Iterator<Item=impl Iterator<Item=GridIx> + '_> + '_ {
+        (0..self.rows()).map(|row| self.line(GridIx::new_u(row, 0), GridIx::new(0, 1)))
+    }
+
+    /// Each column from top to bottom, left to right.
+    fn col_lines(&self) -> impl Iterator<Item=impl Iterator<Item=GridIx> + '_> + '_ {
+        (0..self.cols()).map(|col| self.line(GridIx::new_u(0, col), GridIx::new(1, 0)))
+    }
+
+    /// Each diagonal going down and right, starting from the bottom left corner.
+    fn diagonal_lines(&self) -> impl Iterator<Item=impl Iterator<Item=GridIx> + '_> + '_ {
+        let rows = if self.cols() == 0 { 0 } else { self.rows() };
+        let starts_left = (1..rows).rev().map(|row| GridIx::new_u(row, 0));
+        let starts_top = (0..self.cols()).map(|col| GridIx::new_u(0, col));
+        starts_left.chain(starts_top).map(|start| self.line(start, GridIx::new(1, 1)))
+    }
+
+    /// Each diagonal going down and left, starting from the top left corner.
+    fn anti_diagonal_lines(&self) -> impl Iterator<Item=impl Iterator<Item=GridIx> + '_> + '_ {
+        let cols = self.cols();
+        let rows = if cols == 0 { 0 } else { self.rows() };
+        let starts_top = (0..cols).map(|col| GridIx::new_u(0, col));
+        let starts_right = (1..rows).map(move |row| GridIx::new_u(row, cols - 1));
+        starts_top.chain(starts_right).map(|start| self.line(start, GridIx::new(1, -1)))
+    }
+
+    /// Number of orthogonal steps from `start` to each reachable cell, moving only where `can_step(from, to)`.
+    fn bfs_distances<P>(&self, start: GridIx, mut can_step: P) -> OwnedGrid<Option<usize>>
+    where P: FnMut(GridIx, GridIx) -> bool
+    {
+        let mut distances = OwnedGrid::new(self.rows(), self.cols(), None);
+        let mut queue = VecDeque::from([start]);
+        distances[start] = Some(0);
+        while let Some(ix) = queue.pop_front() {
+            let next = distances[ix].map(|d| d + 1);
+            for n in self.neighbours4(ix) {
+                if distances[n].is_none() && can_step(ix, n) {
+                    distances[n] = next;
+                    queue.push_back(n);
+                }
+            }
+        }
+        distances
+    }
+}
+
+/// Size of a grid without any cells, e.g. a wrapping space where only positions are tracked.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Torus {
+    pub rows: usize,
+    pub cols: usize,
+}
+
+impl GridBounds for Torus {
+    fn rows(&self) -> usize {
+        self.rows
+    }
+
+    fn cols(&self) -> usize {
+        self.cols
+    }
+}
+
+/// Read access to the cells of a grid.
+pub trait GridCells: GridBounds {
+    type Cell<'s> where Self: 's;
+
+    /// The cell at `ix`, or `None` if it is out of bounds.
+    fn cell(&self, ix: GridIx) -> Option<Self::Cell<'_>>;
+
+    /// Cells at the given positions, e.g. one of the [`GridBounds::row_lines`].
+    fn cells<I>(&self, positions: I) -> impl Iterator<Item=Self::Cell<'_>>
+    where I: IntoIterator<Item=GridIx>
+    {
+        positions.into_iter().map(|ix| self.cell(ix).expect("col and row must be within range"))
+    }
+
+    /// Zero-copy view of the whole grid, which can be transformed or cut.
+    fn view(&self) -> GridView<'_, Self>
+    where Self: Sized
+    {
+        GridView {
+            grid: self,
+            rows: self.rows(),
+            cols: self.cols(),
+            origin: GridIx::new(0, 0),
+            row_step: GridIx::new(1, 0),
+            col_step: GridIx::new(0, 1),
+        }
+    }
+
+    /// Labels the connected components of orthogonally adjacent cells for which `same` holds.
+    ///
+    /// Labels are numbered in row-major order of each region's first cell.
+    fn label_regions<'s, F>(&'s self, mut same: F) -> Regions
+    where F: FnMut(Self::Cell<'s>, Self::Cell<'s>) -> bool
+    {
+        const UNLABELED: usize = usize::MAX;
+        let mut labels = OwnedGrid::new(self.rows(), self.cols(), UNLABELED);
+        let mut regions: Vec<Region> = Vec::new();
+        let mut stack = Vec::new();
+        for start in self.row_lines().flatten() {
+            if labels[start] != UNLABELED {
+                continue;
+            }
+            let label = regions.len();
+            let mut region = Region { label, start, area: 0, perimeter: 0, sides: 0, bounds: (start, start) };
+            labels[start] = label;
+            stack.push(start);
+            while let Some(ix) = stack.pop() {
+                region.area += 1;
+                region.bounds = extend_bounds(Some(region.bounds), ix).unwrap();
+                for n in self.neighbours4(ix) {
+                    if labels[n] == UNLABELED && same(self.cell(ix).unwrap(), self.cell(n).unwrap()) {
+                        labels[n] = label;
+                        stack.push(n);
+                    }
+                }
+            }
+            regions.push(region);
+        }
+        for (ix, &label) in labels.iter() {
+            let inside = |n: GridIx| labels.get(n) == Some(&label);
+            regions[label].perimeter += geometry::cell_edges(ix, inside);
+            regions[label].sides += geometry::cell_corners(ix, inside);
+        }
+        Regions { labels, regions }
+    }
+}
+
+/// Connected region of a grid found by [`GridCells::label_regions`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Region {
+    pub label: usize,
+    /// First cell of the region in row-major order.
+    pub start: GridIx,
+    pub area: usize,
+    /// Number of cell edges between the region and the outside, including holes.
+    pub perimeter: usize,
+    /// Number of straight sides of the outline, including holes.
+    pub sides: usize,
+    /// Inclusive top left and bottom right corners.
+    pub bounds: (GridIx, GridIx),
+}
+
+/// Labelled connected components of a grid, see [`GridCells::label_regions`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Regions {
+    /// Label of the region each cell belongs to, indexing into `regions`.
+    pub labels: OwnedGrid<usize>,
+    pub regions: Vec<Region>,
+}
+
+impl GridCells for Grid<'_> {
+    type Cell<'s> = char where Self: 's;
+
+    fn cell(&self, ix: GridIx) -> Option<char> {
+        self.try_get(ix.row, ix.col)
+    }
+}
+
+impl GridBounds for Grid<'_> {
+    fn rows(&self) -> usize {
+        self.rows
+    }
+
+    fn cols(&self) -> usize {
+        self.cols
+    }
+}
+
+/// Owned, mutable grid of arbitrary cells stored row by row.
+///
+/// Unlike [`Grid`], which borrows the puzzle text and hands out `char`s,
+/// the cells can be any type and can be modified in place.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct OwnedGrid<T> {
+    pub rows: usize,
+    pub cols: usize,
+    pub cells: Vec<T>,
+}
+
+impl<T> OwnedGrid<T> {
+    /// Creates a grid with every cell set to `value`.
+    pub fn new(rows: usize, cols: usize, value: T) -> Self
+    where T: Clone
+    {
+        OwnedGrid { rows, cols, cells: vec![value; rows * cols] }
+    }
+
+    /// Creates a grid by calling `f` with the position of each cell.
+    pub fn from_fn<F>(rows: usize, cols: usize, mut f: F) -> Self
+    where F: FnMut(GridIx) -> T
+    {
+        let cells = (0..rows)
+            .flat_map(|row| (0..cols).map(move |col| GridIx::new_u(row, col)))
+            .map(&mut f)
+            .collect();
+        OwnedGrid { rows, cols, cells }
+    }
+
+    /// Parses the puzzle text, mapping each character to a cell.
+    ///
+    /// Panics if the text is not a valid [`Grid`].
+    pub fn parse<F>(input: &str, f: F) -> Self
+    where F: FnMut(char) -> T
+    {
+        Self::try_parse(input, f).unwrap_or_else(|e| panic!("invalid grid: {e}"))
+    }
+
+    /// Parses the puzzle text like [`Grid::try_new`], mapping each character to a cell.
+    pub fn try_parse<F>(input: &str, mut f: F) -> Result<Self, GridError>
+    where F: FnMut(char) -> T
+    {
+        let grid = Grid::try_new(input)?;
+        Ok(Self::from_fn(grid.rows, grid.cols, |ix| f(grid.get(ix.row as usize, ix.col as usize))))
+    }
+
+    fn offset(&self, ix: GridIx) -> Option<usize> {
+        if ix.row < 0 || ix.col < 0 || ix.row as usize >= self.rows || ix.col as usize >= self.cols {
+            return None;
+        }
+        Some(ix.row as usize * self.cols + ix.col as usize)
+    }
+
+    pub fn get(&self, ix: GridIx) -> Option<&T> {
+        self.offset(ix).map(|i| &self.cells[i])
+    }
+
+    pub fn get_mut(&mut self, ix: GridIx) -> Option<&mut T> {
+        self.offset(ix).map(|i| &mut self.cells[i])
+    }
+
+    /// Replaces the cell at `ix`, returning the previous value or `None` if out of bounds.
+    pub fn set(&mut self, ix: GridIx, value: T) -> Option<T> {
+        self.get_mut(ix).map(|cell| std::mem::replace(cell, value))
+    }
+
+    /// Iterates over all cells with their positions in row-major order.
+    pub fn iter(&self) -> impl Iterator<Item=(GridIx, &T)> + '_ {
+        let cols = self.cols;
+        self.cells
+            .iter()
+            .enumerate()
+            .map(move |(i, c)| (GridIx::new_u(i / cols, i % cols), c))
+    }
+
+    /// Position of the first cell equal to `value` in row-major order.
+    pub fn find(&self, value: &T) -> Option<GridIx>
+    where T: PartialEq
+    {
+        self.iter().find(|&(_, c)| c == value).map(|(ix, _)| ix)
+    }
+
+    /// Positions of all cells matching the predicate in row-major order.
+    pub fn find_all<'s, P>(&'s self, mut predicate: P) -> impl Iterator<Item=GridIx> + 's
+    where P: FnMut(&T) -> bool + 's
+    {
+        self.iter().filter(move |&(_, c)| predicate(c)).map(|(ix, _)| ix)
+    }
+
+    /// Creates a new grid of the same size with `f` applied to each cell.
+    pub fn map<U, F>(&self, f: F) -> OwnedGrid<U>
+    where F: FnMut(&T) -> U
+    {
+        OwnedGrid { rows: self.rows, cols: self.cols, cells: self.cells.iter().map(f).collect() }
+    }
+}
+
+impl Display for OwnedGrid<char> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.render().fmt(f)
+    }
+}
+
+impl<T> OwnedGrid<T> {
+    /// Starts rendering the grid, showing each cell as the character returned by `f`.
+    pub fn render_with<'g, F>(&'g self, f: F) -> Render<impl Fn(GridIx) -> char + 'g>
+    where F: Fn(&T) -> char + 'g
+    {
+        Render::new(self.rows, self.cols, move |ix| f(&self[ix]))
+    }
+}
+
+impl OwnedGrid<char> {
+    /// Starts rendering the grid with its own characters.
+    pub fn render(&self) -> Render<impl Fn(GridIx) -> char + '_> {
+        self.render_with(|&c| c)
+    }
+}
+
+impl Grid<'_> {
+    /// Starts rendering the grid with its own characters.
+    pub fn render(&self) -> Render<impl Fn(GridIx) -> char + '_> {
+        Render::new(self.rows, self.cols, |ix| self.get(ix.row as usize, ix.col as usize))
+    }
+}
+
+impl Display for Grid<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.render().fmt(f)
+    }
+}
+
+/// Text rendering of a grid with highlighted positions drawn on top, for debugging.
+///
+/// Rows are separated by newlines, without a trailing one. When several overlays
+/// cover the same position, the one added last wins.
+///
+/// ```
+/// # use advent_of_code::{Grid, GridIx};
+/// # use advent_of_code::template::ANSI_RED;
+/// let grid = Grid::new("...\n...\n");
+/// let guard = [GridIx::new(1, 1)];
+/// println!("{}", grid.render().highlight(guard, '^').colour(guard, ANSI_RED));
+/// ```
+pub struct Render<F> {
+    // position drawn in the top left corner
+    origin: GridIx,
+    rows: usize,
+    cols: usize,
+    cell: F,
+    overlays: Vec<Overlay>,
+}
+
+struct Overlay {
+    positions: HashSet<GridIx>,
+    symbol: Option<char>,
+    colour: Option<&'static str>,
+}
+
+impl<F: Fn(GridIx) -> char> Render<F> {
+    pub fn new(rows: usize, cols: usize, cell: F) -> Self {
+        Render::new_at(GridIx::new(0, 0), rows, cols, cell)
+    }
+
+    /// Renders the area with `origin` in the top left corner, e.g. for grids with negative positions.
+    pub fn new_at(origin: GridIx, rows: usize, cols: usize, cell: F) -> Self {
+        Render { origin, rows, cols, cell, overlays: Vec::new() }
+    }
+
+    /// Draws the positions with the given symbol and optional ANSI colour from [`template`].
+    pub fn overlay<I>(mut self, positions: I, symbol: Option<char>, colour: Option<&'static str>) -> Self
+    where I: IntoIterator<Item=GridIx>
+    {
+        let positions = positions.into_iter().collect();
+        self.overlays.push(Overlay { positions, symbol, colour });
+        self
+    }
+
+    /// Draws the positions with the given symbol.
+    pub fn highlight<I>(self, positions: I, symbol: char) -> Self
+    where I: IntoIterator<Item=GridIx>
+    {
+        self.overlay(positions, Some(symbol), None)
+    }
+
+    /// Keeps the characters at the positions, but draws them in an ANSI colour.
+    pub fn colour<I>(self, positions: I, colour: &'static str) -> Self
+    where I: IntoIterator<Item=GridIx>
+    {
+        self.overlay(positions, None, Some(colour))
+    }
+}
+
+impl<F: Fn(GridIx) -> char> Display for Render<F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for row in 0..self.rows {
+            if row > 0 {
+                writeln!(f)?;
+            }
+            for col in 0..self.cols {
+                let ix = self.origin + GridIx::new_u(row, col);
+                let mut c = (self.cell)(ix);
+                let mut colour = None;
+                for overlay in self.overlays.iter().filter(|o| o.positions.contains(&ix)) {
+                    c = overlay.symbol.unwrap_or(c);
+                    colour = overlay.colour.or(colour);
+                }
+                match colour {
+                    Some(colour) => write!(f, "{colour}{c}{}", template::ANSI_RESET)?,
+                    None => write!(f, "{c}")?,
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<T> GridBounds for OwnedGrid<T> {
+    fn rows(&self) -> usize {
+        self.rows
+    }
+
+    fn cols(&self) -> usize {
+        self.cols
+    }
+}
+
+impl<T> GridCells for OwnedGrid<T> {
+    type Cell<'s> = &'s T where T: 's;
+
+    fn cell(&self, ix: GridIx) -> Option<&T> {
+        self.get(ix)
+    }
+}
+
+impl<T> Index<GridIx> for OwnedGrid<T> {
+    type Output = T;
+
+    fn index(&self, ix: GridIx) -> &T {
+        self.get(ix).expect("col and row must be within range")
+    }
+}
+
+impl<T> IndexMut<GridIx> for OwnedGrid<T> {
+    fn index_mut(&mut self, ix: GridIx) -> &mut T {
+        self.get_mut(ix).expect("col and row must be within range")
+    }
+}
+
+impl OwnedGrid<char> {
+    /// Renders the cells back to puzzle text that [`Grid::new`] can read.
+    pub fn to_text(&self) -> String {
+        let mut text = String::with_capacity(self.rows * (self.cols + 1));
+        for row in self.cells.chunks(self.cols.max(1)) {
+            text.extend(row);
+            text.push('\n');
+        }
+        text
+    }
+}
+
+impl From<&Grid<'_>> for OwnedGrid<char> {
+    fn from(grid: &Grid) -> Self {
+        Self::from_fn(grid.rows, grid.cols, |ix| grid.get(ix.row as usize, ix.col as usize))
+    }
+}
+
+/// Unbounded grid storing only the cells that were set, where every other cell has a default value.
+///
+/// Positions may be negative. The bounding box of the set cells is tracked as they change.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SparseGrid<T> {
+    cells: HashMap<GridIx, T>,
+    default: T,
+    // inclusive top left and bottom right corners of the set cells
+    bounds: Option<(GridIx, GridIx)>,
+}
+
+fn extend_bounds(bounds: Option<(GridIx, GridIx)>, ix: GridIx) -> Option<(GridIx, GridIx)> {
+    Some(match bounds {
+        None => (ix, ix),
+        Some((min, max)) => (
+            GridIx::new(min.row.min(ix.row), min.col.min(ix.col)),
+            GridIx::new(max.row.max(ix.row), max.col.max(ix.col)),
+        ),
+    })
+}
+
+impl<T> SparseGrid<T> {
+    pub fn new(default: T) -> Self {
+        SparseGrid { cells: HashMap::new(), default, bounds: None }
+    }
+
+    /// Parses the puzzle text, setting the cells for which `f` returns a value.
+    pub fn parse<F>(input: &str, default: T, mut f: F) -> Self
+    where F: FnMut(char) -> Option<T>
+    {
+        let mut grid = SparseGrid::new(default);
+        for (ix, c) in Grid::new(input).iter() {
+            if let Some(value) = f(c) {
+                grid.set(ix, value);
+            }
+        }
+        grid
+    }
+
+    /// The cell at `ix`, or the default value if it was not set.
+    pub fn get(&self, ix: GridIx) -> &T {
+        self.cells.get(&ix).unwrap_or(&self.default)
+    }
+
+    pub fn contains(&self, ix: GridIx) -> bool {
+        self.cells.contains_key(&ix)
+    }
+
+    /// Sets the cell at `ix`, returning the previous value if it was set.
+    pub fn set(&mut self, ix: GridIx, value: T) -> Option<T> {
+        self.bounds = extend_bounds(self.bounds, ix);
+        self.cells.insert(ix, value)
+    }
+
+    /// Resets the cell at `ix` to the default, returning the previous value if it was set.
+    pub fn remove(&mut self, ix: GridIx) -> Option<T> {
+        let value = self.cells.remove(&ix)?;
+        if self.bounds.is_some_and(|(min, max)| [min.row, max.row].contains(&ix.row) || [min.col, max.col].contains(&ix.col)) {
+            self.bounds = self.cells.keys().fold(None, |bounds, &ix| extend_bounds(bounds, ix));
+        }
+        Some(value)
+    }
+
+    /// Inclusive top left and bottom right corners of the set cells, or `None` if there are none.
+    pub fn bounds(&self) -> Option<(GridIx, GridIx)> {
+        self.bounds
+    }
+
+    /// Number of set cells.
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// Iterates over the set cells in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item=(GridIx, &T)> + '_ {
+        self.cells.iter().map(|(&ix, value)| (ix, value))
+    }
+
+    /// Orthogonal neighbours of `ix` with their values.
+    pub fn neighbours4(&self, ix: GridIx) -> impl Iterator<Item=(GridIx, &T)> + '_ {
+        ix.neighbours4().into_iter().map(|n| (n, self.get(n)))
+    }
+
+    /// Orthogonal and diagonal neighbours of `ix` with their values.
+    pub fn neighbours8(&self, ix: GridIx) -> impl Iterator<Item=(GridIx, &T)> + '_ {
+        ix.neighbours8().into_iter().map(|n| (n, self.get(n)))
+    }
+
+    /// Starts rendering the bounding box, showing each cell as the character returned by `f`.
+    pub fn render_with<'g, F>(&'g self, f: F) -> Render<impl Fn(GridIx) -> char + 'g>
+    where F: Fn(&T) -> char + 'g
+    {
+        let (min, max) = self.bounds.unwrap_or((GridIx::new(0, 0), GridIx::new(-1, -1)));
+        let size = max - min + GridIx::new(1, 1);
+        Render::new_at(min, size.row as usize, size.col as usize, move |ix| f(self.get(ix)))
+    }
+}
+
+impl SparseGrid<char> {
+    /// Starts rendering the bounding box with the cells' own characters.
+    pub fn render(&self) -> Render<impl Fn(GridIx) -> char + '_> {
+        self.render_with(|&c| c)
+    }
+}
+
+impl Display for SparseGrid<char> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.render().fmt(f)
+    }
+}
+
+/// Fixed-size set of indices stored as one bit each.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct BitSet {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl BitSet {
+    fn new(size: usize) -> Self {
+        BitSet { words: vec![0; size.div_ceil(64)], len: 0 }
+    }
+
+    fn contains(&self, i: usize) -> bool {
+        self.words[i / 64] & (1 << (i % 64)) != 0
+    }
+
+    fn insert(&mut self, i: usize) -> bool {
+        let missing = !self.contains(i);
+        self.words[i / 64] |= 1 << (i % 64);
+        self.len += missing as usize;
+        missing
+    }
+
+    fn remove(&mut self, i: usize) -> bool {
+        let present = self.contains(i);
+        self.words[i / 64] &= !(1 << (i % 64));
+        self.len -= present as usize;
+        present
+    }
+
+    fn clear(&mut self) {
+        self.words.fill(0);
+        self.len = 0;
+    }
+
+    fn iter(&self) -> impl Iterator<Item=usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(w, &word)| {
+            (0..64).filter(move |b| word & (1 << b) != 0).map(move |b| w * 64 + b)
+        })
+    }
+}
+
+/// Set of positions within a grid, stored as one bit per cell.
+///
+/// Meant to replace a `HashSet<GridIx>` in hot loops; [`GridSet::clear`] keeps the allocation.
+/// Inserting a position outside the grid panics.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct GridSet {
+    rows: usize,
+    cols: usize,
+    bits: BitSet,
+}
+
+impl GridSet {
+    pub fn new(rows: usize, cols: usize) -> Self {
+        GridSet { rows, cols, bits: BitSet::new(rows * cols) }
+    }
+
+    /// Creates an empty set with the same size as `grid`.
+    pub fn for_grid<G: GridBounds>(grid: &G) -> Self {
+        GridSet::new(grid.rows(), grid.cols())
+    }
+
+    fn offset(&self, ix: GridIx) -> Option<usize> {
+        ix.is_within(self).then(|| ix.row as usize * self.cols + ix.col as usize)
+    }
+
+    pub fn contains(&self, ix: GridIx) -> bool {
+        self.offset(ix).is_some_and(|i| self.bits.contains(i))
+    }
+
+    /// Adds the position, returning whether it was not present yet.
+    pub fn insert(&mut self, ix: GridIx) -> bool {
+        let i = self.offset(ix).expect("col and row must be within range");
+        self.bits.insert(i)
+    }
+
+    /// Removes the position, returning whether it was present.
+    pub fn remove(&mut self, ix: GridIx) -> bool {
+        self.offset(ix).is_some_and(|i| self.bits.remove(i))
+    }
+
+    pub fn len(&self) -> usize {
+        self.bits.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bits.len == 0
+    }
+
+    /// Removes all positions, keeping the allocated memory.
+    pub fn clear(&mut self) {
+        self.bits.clear()
+    }
+
+    /// Iterates over the positions in row-major order.
+    pub fn iter(&self) -> impl Iterator<Item=GridIx> + '_ {
+        self.bits.iter().map(|i| GridIx::new_u(i / self.cols, i % self.cols))
+    }
+}
+
+impl GridBounds for GridSet {
+    fn rows(&self) -> usize {
+        self.rows
+    }
+
+    fn cols(&self) -> usize {
+        self.cols
+    }
+}
+
+impl Extend<GridIx> for GridSet {
+    fn extend<I: IntoIterator<Item=GridIx>>(&mut self, iter: I) {
+        for ix in iter {
+            self.insert(ix);
         }
     }
+}
+
+/// Set of positions within a grid together with a [`Direction`], e.g. visited states of a walker.
+///
+/// Stored as one bit for each cell and direction, like the [`GridSet`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct GridDirSet {
+    rows: usize,
+    cols: usize,
+    bits: BitSet,
+}
+
+impl GridDirSet {
+    pub fn new(rows: usize, cols: usize) -> Self {
+        GridDirSet { rows, cols, bits: BitSet::new(rows * cols * Direction::ALL.len()) }
+    }
+
+    /// Creates an empty set with the same size as `grid`.
+    pub fn for_grid<G: GridBounds>(grid: &G) -> Self {
+        GridDirSet::new(grid.rows(), grid.cols())
+    }
+
+    fn offset(&self, ix: GridIx, dir: Direction) -> Option<usize> {
+        ix.is_within(self).then(|| (ix.row as usize * self.cols + ix.col as usize) * Direction::ALL.len() + dir as usize)
+    }
+
+    pub fn contains(&self, ix: GridIx, dir: Direction) -> bool {
+        self.offset(ix, dir).is_some_and(|i| self.bits.contains(i))
+    }
+
+    /// Adds the state, returning whether it was not present yet.
+    pub fn insert(&mut self, ix: GridIx, dir: Direction) -> bool {
+        let i = self.offset(ix, dir).expect("col and row must be within range");
+        self.bits.insert(i)
+    }
+
+    /// Removes the state, returning whether it was present.
+    pub fn remove(&mut self, ix: GridIx, dir: Direction) -> bool {
+        self.offset(ix, dir).is_some_and(|i| self.bits.remove(i))
+    }
+
+    pub fn len(&self) -> usize {
+        self.bits.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bits.len == 0
+    }
+
+    /// Removes all states, keeping the allocated memory.
+    pub fn clear(&mut self) {
+        self.bits.clear()
+    }
+
+    /// Iterates over the states in row-major order of their positions.
+    pub fn iter(&self) -> impl Iterator<Item=(GridIx, Direction)> + '_ {
+        let n = Direction::ALL.len();
+        self.bits.iter().map(move |i| {
+            let cell = i / n;
+            (GridIx::new_u(cell / self.cols, cell % self.cols), Direction::ALL[i % n])
+        })
+    }
+}
 
-    pub fn is_within(&self, grid: &Grid) -> bool {
-        0 <= self.row && (self.row as usize) < grid.rows
-            && 0 <= self.col && (self.col as usize) < grid.cols
+impl GridBounds for GridDirSet {
+    fn rows(&self) -> usize {
+        self.rows
     }
-}
\ No newline at end of file
+
+    fn cols(&self) -> usize {
+        self.cols
+    }
+}
+
+/// Transformed or cut view of a grid, which maps its positions onto the underlying grid.
+///
+/// Transformations compose, so e.g. `grid.view().transpose().flip_vertical()`
+/// is the grid rotated counter-clockwise. No cells are copied.
+pub struct GridView<'g, G> {
+    grid: &'g G,
+    rows: usize,
+    cols: usize,
+    // underlying position of the view's top left corner
+    origin: GridIx,
+    // underlying step when moving one row down or one column right in the view
+    row_step: GridIx,
+    col_step: GridIx,
+}
+
+impl<G> Clone for GridView<'_, G> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<G> Copy for GridView<'_, G> {}
+
+impl<'g, G: GridCells> GridView<'g, G> {
+    /// Position in the underlying grid corresponding to `ix` in this view.
+    pub fn source(&self, ix: GridIx) -> GridIx {
+        self.origin + self.row_step * ix.row + self.col_step * ix.col
+    }
+
+    /// Swaps rows and columns, mirroring along the main diagonal.
+    pub fn transpose(self) -> Self {
+        GridView {
+            rows: self.cols,
+            cols: self.rows,
+            row_step: self.col_step,
+            col_step: self.row_step,
+            ..self
+        }
+    }
+
+    /// Mirrors left to right.
+    pub fn flip_horizontal(self) -> Self {
+        GridView {
+            origin: self.origin + self.col_step * (self.cols as isize - 1),
+            col_step: -self.col_step,
+            ..self
+        }
+    }
+
+    /// Mirrors top to bottom.
+    pub fn flip_vertical(self) -> Self {
+        GridView {
+            origin: self.origin + self.row_step * (self.rows as isize - 1),
+            row_step: -self.row_step,
+            ..self
+        }
+    }
+
+    /// Rotates 90° clockwise.
+    pub fn rotate_cw(self) -> Self {
+        self.flip_vertical().transpose()
+    }
+
+    /// Rotates 90° counter-clockwise.
+    pub fn rotate_ccw(self) -> Self {
+        self.transpose().flip_vertical()
+    }
+
+    pub fn rotate180(self) -> Self {
+        self.flip_vertical().flip_horizontal()
+    }
+
+    /// The four rotations of this view, starting with itself and turning clockwise.
+    pub fn rotations(self) -> [Self; 4] {
+        [self, self.rotate_cw(), self.rotate180(), self.rotate_ccw()]
+    }
+
+    /// The eight rotations and reflections of this view, starting with the [`GridView::rotations`].
+    pub fn symmetries(self) -> [Self; 8] {
+        let [a, b, c, d] = self.rotations();
+        let [e, f, g, h] = self.flip_horizontal().rotations();
+        [a, b, c, d, e, f, g, h]
+    }
+
+    /// Rectangular window of this view with `top_left` at its origin, or `None` if it does not fit.
+    pub fn window(self, top_left: GridIx, rows: usize, cols: usize) -> Option<Self> {
+        let fits = top_left.row >= 0 && top_left.col >= 0
+            && top_left.row as usize + rows <= self.rows
+            && top_left.col as usize + cols <= self.cols;
+        fits.then(|| GridView { origin: self.source(top_left), rows, cols, ..self })
+    }
+}
+
+impl<G> GridBounds for GridView<'_, G> {
+    fn rows(&self) -> usize {
+        self.rows
+    }
+
+    fn cols(&self) -> usize {
+        self.cols
+    }
+}
+
+impl<'g, G: GridCells> GridCells for GridView<'g, G> {
+    type Cell<'s> = G::Cell<'g> where Self: 's;
+
+    fn cell(&self, ix: GridIx) -> Option<G::Cell<'g>> {
+        if !ix.is_within(self) {
+            return None;
+        }
+        self.grid.cell(self.source(ix))
+    }
+}
+
+impl<'g, G: GridCells> GridView<'g, G> {
+    /// Collects the view into a new owned grid, with `f` applied to each cell.
+    pub fn map<T, F>(&self, mut f: F) -> OwnedGrid<T>
+    where F: FnMut(G::Cell<'g>) -> T
+    {
+        OwnedGrid::from_fn(self.rows, self.cols, |ix| f(self.cell(ix).unwrap()))
+    }
+}
+
+impl GridView<'_, Grid<'_>> {
+    /// Collects the view into a new owned grid.
+    pub fn to_owned_grid(&self) -> OwnedGrid<char> {
+        self.map(|c| c)
+    }
+}
+
+/// Small 2D template of characters to search for in a grid, where `None` cells match anything.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Pattern {
+    pub cells: OwnedGrid<Option<char>>,
+}
+
+impl Pattern {
+    /// Reads the template from text, treating the `wildcard` character as matching anything.
+    pub fn new(text: &str, wildcard: char) -> Self {
+        Pattern { cells: OwnedGrid::parse(text, |c| (c != wildcard).then_some(c)) }
+    }
+
+    /// Whether the template matches with its top left corner at `anchor`.
+    pub fn matches_at<'s, G>(&self, grid: &'s G, anchor: GridIx) -> bool
+    where G: GridCells<Cell<'s>=char>
+    {
+        self.cells.iter().all(|(ix, &c)| match c {
+            Some(c) => grid.cell(anchor + ix) == Some(c),
+            None => (anchor + ix).is_within(grid),
+        })
+    }
+
+    /// Top left corners of all matches in row-major order.
+    pub fn find_in<'s, G>(&self, grid: &'s G) -> Vec<GridIx>
+    where G: GridCells<Cell<'s>=char>
+    {
+        let rows = (grid.rows() + 1).saturating_sub(self.cells.rows);
+        let cols = (grid.cols() + 1).saturating_sub(self.cells.cols);
+        (0..rows)
+            .flat_map(|row| (0..cols).map(move |col| GridIx::new_u(row, col)))
+            .filter(|&anchor| self.matches_at(grid, anchor))
+            .collect()
+    }
+
+    /// The distinct rotations and reflections of this template.
+    pub fn orientations(&self) -> Vec<Pattern> {
+        let mut orientations: Vec<Pattern> = Vec::new();
+        for view in self.cells.view().symmetries() {
+            let pattern = Pattern { cells: view.map(|&c| c) };
+            if !orientations.contains(&pattern) {
+                orientations.push(pattern);
+            }
+        }
+        orientations
+    }
+}
+
+impl Grid<'_> {
+    /// Top left corners of all matches of the template in row-major order.
+    pub fn find_pattern(&self, pattern: &Pattern) -> Vec<GridIx> {
+        pattern.find_in(self)
+    }
+
+    /// Top left corners where any rotation or reflection of the template matches, in row-major order.
+    pub fn find_pattern_any_orientation(&self, pattern: &Pattern) -> Vec<GridIx> {
+        let mut anchors = pattern.orientations()
+            .iter()
+            .flat_map(|p| p.find_in(self))
+            .collect::<Vec<_>>();
+        anchors.sort();
+        anchors.dedup();
+        anchors
+    }
+}
+
+/// Position and facing direction of something moving around a grid.
+pub type Walker = (GridIx, Direction);
+
+/// Weighted search over walkers moving around a grid, e.g. a reindeer that pays extra for turning.
+///
+/// From each state, the walker can step forward into the next cell if `passable(from, to)`,
+/// or turn 90° either way in place. Every move is priced by `cost(from, to)`.
+pub struct GridSearch<P, C> {
+    rows: usize,
+    cols: usize,
+    passable: P,
+    cost: C,
+}
+
+impl<P, C> GridSearch<P, C>
+where P: Fn(GridIx, GridIx) -> bool, C: Fn(Walker, Walker) -> usize
+{
+    pub fn new<G: GridBounds>(grid: &G, passable: P, cost: C) -> Self {
+        GridSearch { rows: grid.rows(), cols: grid.cols(), passable, cost }
+    }
+
+    /// The states reachable in one move with their costs.
+    pub fn successors(&self, (ix, dir): Walker) -> Vec<(Walker, usize)> {
+        let next = ix + dir.delta();
+        let forward = (next.is_within(self) && (self.passable)(ix, next)).then_some((next, dir));
+        forward
+            .into_iter()
+            .chain([(ix, dir.cw()), (ix, dir.ccw())])
+            .map(|to| (to, (self.cost)((ix, dir), to)))
+            .collect()
+    }
+
+    /// Cheapest cost of reaching every reachable state from `start`.
+    pub fn distances(&self, start: Walker) -> HashMap<Walker, usize> {
+        paths::dijkstra_predecessors(start, |&s| self.successors(s), |_| false).0
+    }
+
+    /// A cheapest path to a position satisfying `is_goal`, with its cost.
+    pub fn shortest_path<F>(&self, start: Walker, is_goal: F) -> Option<(Vec<Walker>, usize)>
+    where F: Fn(GridIx) -> bool
+    {
+        pathfinding::prelude::dijkstra(&start, |&s| self.successors(s), |s| is_goal(s.0))
+    }
+
+    /// A cheapest path using A*, where `heuristic` must never overestimate the remaining cost.
+    pub fn shortest_path_astar<F, H>(&self, start: Walker, is_goal: F, heuristic: H) -> Option<(Vec<Walker>, usize)>
+    where F: Fn(GridIx) -> bool, H: Fn(Walker) -> usize
+    {
+        pathfinding::prelude::astar(&start, |&s| self.successors(s), |&s| heuristic(s), |s| is_goal(s.0))
+    }
+
+    /// All cheapest paths to positions satisfying `is_goal`.
+    pub fn all_shortest_paths<F>(&self, start: Walker, is_goal: F) -> Option<ShortestPaths<Walker>>
+    where F: Fn(GridIx) -> bool
+    {
+        paths::all_shortest_paths(start, |&s| self.successors(s), |s| is_goal(s.0))
+    }
+
+    /// The cheapest cost of reaching a goal together with every cell on any cheapest path.
+    pub fn optimal_cells<F>(&self, start: Walker, is_goal: F) -> Option<(usize, GridSet)>
+    where F: Fn(GridIx) -> bool
+    {
+        let paths = self.all_shortest_paths(start, is_goal)?;
+        let mut cells = GridSet::new(self.rows, self.cols);
+        cells.extend(paths.nodes().into_iter().map(|(ix, _)| ix));
+        Some((paths.cost(), cells))
+    }
+}
+
+impl<P, C> GridBounds for GridSearch<P, C> {
+    fn rows(&self) -> usize {
+        self.rows
+    }
+
+    fn cols(&self) -> usize {
+        self.cols
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &str = "ab\ncd\nef\n";
+
+    #[test]
+    fn test_owned_grid() {
+        let mut grid = OwnedGrid::parse(TEXT, |c| c);
+        assert_eq!((grid.rows, grid.cols), (3, 2));
+        assert_eq!(grid.get(GridIx::new(1, 0)), Some(&'c'));
+        assert_eq!(grid.get(GridIx::new(0, 2)), None);
+        assert_eq!(grid.set(GridIx::new(2, 1), 'x'), Some('f'));
+        *grid.get_mut(GridIx::new(0, 0)).unwrap() = 'y';
+        assert_eq!(grid[GridIx::new(2, 1)], 'x');
+        assert_eq!(grid.to_text(), "yb\ncd\nex\n");
+    }
+
+    #[test]
+    fn test_owned_grid_conversion() {
+        let grid = Grid::new(TEXT);
+        let owned = OwnedGrid::from(&grid);
+        assert_eq!(owned.to_text(), TEXT);
+        let text = owned.to_text();
+        assert_eq!(Grid::new(&text).row(2), "ef");
+        let digits = OwnedGrid::parse(TEXT, |c| c as u32 - 'a' as u32);
+        assert_eq!(digits.map(|d| d * 2)[GridIx::new(2, 1)], 10);
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let grid = Grid::new(TEXT);
+        let corner = grid.neighbours4(GridIx::new(0, 0)).collect::<Vec<_>>();
+        assert_eq!(corner, [GridIx::new(0, 1), GridIx::new(1, 0)]);
+        assert_eq!(grid.neighbours4(GridIx::new(1, 1)).count(), 3);
+        assert_eq!(grid.neighbours8(GridIx::new(1, 1)).count(), 5);
+        assert_eq!(GridIx::new(1, 1).neighbours8().len(), 8);
+    }
+
+    #[test]
+    fn test_grid_line_endings() {
+        for text in ["ab\ncd", "ab\r\ncd\r\n", "ab\ncd\n\n"] {
+            let grid = Grid::try_new(text).unwrap();
+            assert_eq!((grid.rows, grid.cols), (2, 2), "{text:?}");
+            assert_eq!(grid.row(1), "cd", "{text:?}");
+            assert_eq!(grid.col(1), "bd", "{text:?}");
+        }
+        assert_eq!(Grid::try_new("").map(|g| g.rows), Ok(0));
+    }
+
+    #[test]
+    fn test_grid_errors() {
+        assert_eq!(Grid::try_new("ab\nc\nde\n").err(), Some(GridError::Ragged { line: 2, len: 1, expected: 2 }));
+        assert_eq!(Grid::try_new("ab\r\ncd\nef").err(), Some(GridError::MixedLineEndings { line: 2 }));
+        assert_eq!(Grid::try_new("ab\ncé\n").err(), Some(GridError::NonAscii { line: 2 }));
+        assert_eq!(OwnedGrid::try_parse("a\nbc", |c| c).err(), Some(GridError::Ragged { line: 2, len: 2, expected: 1 }));
+    }
+
+    #[test]
+    fn test_render() {
+        let grid = Grid::new(TEXT);
+        assert_eq!(grid.to_string(), "ab\ncd\nef");
+        let path = [GridIx::new(0, 0), GridIx::new(1, 0)];
+        let render = grid.render()
+            .highlight(path, '*')
+            .highlight([GridIx::new(1, 0)], '@');
+        assert_eq!(render.to_string(), "*b\n@d\nef");
+        let coloured = grid.render().colour([GridIx::new(2, 1)], template::ANSI_RED);
+        assert_eq!(coloured.to_string(), format!("ab\ncd\ne{}f{}", template::ANSI_RED, template::ANSI_RESET));
+        let digits = OwnedGrid::new(1, 3, 7u32);
+        assert_eq!(digits.render_with(|&d| char::from_digit(d, 10).unwrap()).to_string(), "777");
+    }
+
+    fn lines_text<'s, G>(grid: &'s G, lines: impl Iterator<Item=impl Iterator<Item=GridIx>>) -> Vec<String>
+    where G: GridCells<Cell<'s>=char>
+    {
+        lines.map(|line| grid.cells(line).collect()).collect()
+    }
+
+    #[test]
+    fn test_lines() {
+        let grid = Grid::new(TEXT);
+        assert_eq!(lines_text(&grid, grid.row_lines()), ["ab", "cd", "ef"]);
+        assert_eq!(lines_text(&grid, grid.col_lines()), ["ace", "bdf"]);
+        assert_eq!(lines_text(&grid, grid.diagonal_lines()), ["e", "cf", "ad", "b"]);
+        assert_eq!(lines_text(&grid, grid.anti_diagonal_lines()), ["a", "bc", "de", "f"]);
+        let empty = OwnedGrid::new(2, 0, 0u8);
+        assert_eq!(empty.diagonal_lines().count(), 0);
+        assert_eq!(empty.anti_diagonal_lines().count(), 0);
+    }
+
+    #[test]
+    fn test_view() {
+        let grid = Grid::new(TEXT);
+        let view = grid.view();
+        assert_eq!(view.to_owned_grid().to_text(), TEXT);
+        assert_eq!(view.transpose().to_owned_grid().to_text(), "ace\nbdf\n");
+        assert_eq!(view.rotate_cw().to_owned_grid().to_text(), "eca\nfdb\n");
+        assert_eq!(view.rotate_ccw().to_owned_grid().to_text(), "bdf\nace\n");
+        assert_eq!(view.rotate180().to_owned_grid().to_text(), "fe\ndc\nba\n");
+        assert_eq!(view.flip_horizontal().to_owned_grid().to_text(), "ba\ndc\nfe\n");
+        assert_eq!(view.flip_vertical().to_owned_grid().to_text(), "ef\ncd\nab\n");
+        assert_eq!(view.rotate_cw().rotate_cw().rotate_cw().rotate_cw().to_owned_grid().to_text(), TEXT);
+        let distinct = view.symmetries().map(|v| v.to_owned_grid().to_text());
+        assert!(distinct.iter().enumerate().all(|(i, a)| distinct[i + 1..].iter().all(|b| a != b)));
+        let window = view.rotate180().window(GridIx::new(1, 0), 2, 1).unwrap();
+        assert_eq!(window.to_owned_grid().to_text(), "d\nb\n");
+        assert_eq!(window.cell(GridIx::new(0, 1)), None);
+        assert!(view.window(GridIx::new(2, 1), 2, 1).is_none());
+        let owned = OwnedGrid::from(&grid);
+        assert_eq!(owned.view().transpose().cell(GridIx::new(1, 2)), Some(&'f'));
+    }
+
+    #[test]
+    fn test_pattern() {
+        let grid = Grid::new("abab\nbaba\nabab\n");
+        let diagonal = Pattern::new("a?\n?a\n", '?');
+        assert_eq!(grid.find_pattern(&diagonal), [GridIx::new(0, 0), GridIx::new(0, 2), GridIx::new(1, 1)]);
+        let corner = Pattern::new("ab\n.b\n", '.');
+        assert_eq!(corner.orientations().len(), 8);
+        assert_eq!(grid.find_pattern(&corner), []);
+        assert_eq!(grid.find_pattern_any_orientation(&corner), []);
+        let line = Pattern::new("aba\n", '.');
+        assert_eq!(line.orientations().len(), 2);
+        assert_eq!(grid.find_pattern_any_orientation(&line), [GridIx::new(0, 0), GridIx::new(0, 2), GridIx::new(1, 1), GridIx::new(2, 0)]);
+        assert!(grid.find_pattern(&Pattern::new("abab\nbaba\nabab\nbaba\n", '.')).is_empty());
+    }
+
+    #[test]
+    fn test_sparse_grid() {
+        let mut grid = SparseGrid::parse("#.\n.#\n", '.', |c| (c == '#').then_some(c));
+        assert_eq!(grid.len(), 2);
+        assert_eq!(grid.bounds(), Some((GridIx::new(0, 0), GridIx::new(1, 1))));
+        grid.set(GridIx::new(-1, 3), '#');
+        assert_eq!(grid.get(GridIx::new(-1, 3)), &'#');
+        assert_eq!(grid.get(GridIx::new(-5, -5)), &'.');
+        assert_eq!(grid.neighbours8(GridIx::new(0, 1)).filter(|&(_, &c)| c == '#').count(), 2);
+        assert_eq!(grid.to_string(), "...#\n#...\n.#..");
+        assert_eq!(grid.render().highlight([GridIx::new(-1, 0)], 'o').to_string(), "o..#\n#...\n.#..");
+        assert_eq!(grid.remove(GridIx::new(-1, 3)), Some('#'));
+        assert_eq!(grid.remove(GridIx::new(-1, 3)), None);
+        assert_eq!(grid.bounds(), Some((GridIx::new(0, 0), GridIx::new(1, 1))));
+        assert_eq!(SparseGrid::new('.').to_string(), "");
+    }
+
+    #[test]
+    fn test_grid_set() {
+        let grid = Grid::new(TEXT);
+        let mut set = GridSet::for_grid(&grid);
+        assert!(set.insert(GridIx::new(2, 1)));
+        assert!(!set.insert(GridIx::new(2, 1)));
+        set.extend([GridIx::new(0, 1), GridIx::new(1, 0)]);
+        assert_eq!(set.len(), 3);
+        assert!(set.contains(GridIx::new(1, 0)));
+        assert!(!set.contains(GridIx::new(5, 0)));
+        assert!(set.remove(GridIx::new(1, 0)));
+        assert_eq!(set.iter().collect::<Vec<_>>(), [GridIx::new(0, 1), GridIx::new(2, 1)]);
+        set.clear();
+        assert!(set.is_empty());
+        let big = OwnedGrid::new(20, 30, ());
+        let mut set = GridSet::for_grid(&big);
+        set.extend(big.row_lines().flatten().step_by(7));
+        assert_eq!(set.iter().collect::<Vec<_>>(), big.row_lines().flatten().step_by(7).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_grid_dir_set() {
+        let mut set = GridDirSet::for_grid(&Grid::new(TEXT));
+        assert!(set.insert(GridIx::new(1, 1), Direction::W));
+        assert!(set.insert(GridIx::new(1, 1), Direction::N));
+        assert!(!set.insert(GridIx::new(1, 1), Direction::W));
+        assert!(set.contains(GridIx::new(1, 1), Direction::N));
+        assert!(!set.contains(GridIx::new(1, 1), Direction::E));
+        assert_eq!(set.iter().collect::<Vec<_>>(), [(GridIx::new(1, 1), Direction::N), (GridIx::new(1, 1), Direction::W)]);
+        assert!(set.remove(GridIx::new(1, 1), Direction::N));
+        assert_eq!(set.len(), 1);
+        set.clear();
+        assert!(set.is_empty());
+    }
+
+    #[test]
+    fn test_label_regions() {
+        let grid = Grid::new("AAAAA\nABABA\nAAAAA\nCCAAC\n");
+        let Regions { labels, regions } = grid.label_regions(|a, b| a == b);
+        assert_eq!(regions.len(), 5);
+        assert_eq!(labels.row_lines().flatten().filter(|&ix| labels[ix] == 0).count(), 15);
+        let a = &regions[0];
+        assert_eq!((a.area, a.perimeter, a.sides), (15, 18 + 2 * 4, 8 + 2 * 4));
+        assert_eq!(a.bounds, (GridIx::new(0, 0), GridIx::new(3, 4)));
+        let b = &regions[1];
+        assert_eq!((b.start, b.area, b.perimeter, b.sides), (GridIx::new(1, 1), 1, 4, 4));
+        let c = &regions[3];
+        assert_eq!((c.start, c.area, c.perimeter, c.sides, c.bounds.1), (GridIx::new(3, 0), 2, 6, 4, GridIx::new(3, 1)));
+        assert_eq!(labels[GridIx::new(3, 4)], 4);
+        let diagonal = OwnedGrid::parse("x.\n.x\n", |c| c == 'x');
+        assert_eq!(diagonal.label_regions(|a, b| a == b).regions.len(), 4);
+    }
+
+    #[test]
+    fn test_bfs_distances() {
+        let grid = Grid::new("..#\n.##\n...\n");
+        let distances = grid.bfs_distances(GridIx::new(0, 0), |_, to| grid.cell(to) == Some('.'));
+        assert_eq!(distances[GridIx::new(2, 2)], Some(4));
+        assert_eq!(distances[GridIx::new(0, 1)], Some(1));
+        assert_eq!(distances[GridIx::new(0, 2)], None);
+    }
+
+    #[test]
+    fn test_grid_search() {
+        let grid = Grid::new("#####\n#..E#\n#.#.#\n#S..#\n#####\n");
+        let start = (grid.find('S').unwrap(), Direction::E);
+        let end = grid.find('E').unwrap();
+        let search = GridSearch::new(
+            &grid,
+            |_, to| grid.cell(to) != Some('#'),
+            |(a, _), (b, _)| if a == b { 1000 } else { 1 },
+        );
+        let (path, cost) = search.shortest_path(start, |ix| ix == end).unwrap();
+        assert_eq!(cost, 1004);
+        assert_eq!(path.first(), Some(&start));
+        assert_eq!(path.last().map(|s| s.0), Some(end));
+        let heuristic = |(ix, _): Walker| ix.manhattan(&end);
+        assert_eq!(search.shortest_path_astar(start, |ix| ix == end, heuristic).map(|p| p.1), Some(1004));
+        let (best, cells) = search.optimal_cells(start, |ix| ix == end).unwrap();
+        assert_eq!((best, cells.len()), (1004, 5));
+        assert_eq!(search.distances(start)[&(GridIx::new(1, 1), Direction::N)], 1002);
+        assert!(search.optimal_cells(start, |ix| ix == GridIx::new(0, 0)).is_none());
+        let paths = search.all_shortest_paths(start, |ix| ix == end).unwrap();
+        assert_eq!(paths.count(), 1);
+        assert!(paths.paths().all(|p| p.first() == Some(&start) && p.len() == 6));
+    }
+
+    #[test]
+    fn test_find() {
+        let grid = Grid::new("a.b\n.a.\n");
+        assert_eq!(grid.find('b'), Some(GridIx::new(0, 2)));
+        assert_eq!(grid.find('x'), None);
+        let letters = grid.find_all(|c| c.is_alphabetic()).collect::<Vec<_>>();
+        assert_eq!(letters, [GridIx::new(0, 0), GridIx::new(0, 2), GridIx::new(1, 1)]);
+        let by_char = grid.positions_by_char();
+        assert_eq!(by_char[&'a'], [GridIx::new(0, 0), GridIx::new(1, 1)]);
+        assert_eq!(by_char[&'.'].len(), 3);
+        let owned = OwnedGrid::from(&grid);
+        assert_eq!(owned.find(&'b'), Some(GridIx::new(0, 2)));
+        assert_eq!(owned.find_all(|&c| c == '.').count(), 3);
+    }
+
+    #[test]
+    fn test_grid_ix_ops() {
+        let a = GridIx::new(1, -2);
+        let b = GridIx::new(4, 2);
+        assert_eq!(a + b, GridIx::new(5, 0));
+        assert_eq!(b - a, GridIx::new(3, 4));
+        assert_eq!(-a, GridIx::new(-1, 2));
+        assert_eq!(a * 3, GridIx::new(3, -6));
+        let mut c = a;
+        c += b;
+        c -= a * 2;
+        assert_eq!(c, GridIx::new(3, 4));
+        assert_eq!(a.manhattan(&b), 7);
+        assert_eq!(a.chebyshev(&b), 4);
+        assert_eq!((b - a).signum(), GridIx::new(1, 1));
+        assert!(GridIx::new(0, 5) < GridIx::new(1, 0));
+        assert!(GridIx::new(1, 0) < GridIx::new(1, 1));
+    }
+
+    #[test]
+    fn test_wrapping() {
+        let space = Torus { rows: 7, cols: 11 };
+        assert_eq!(space.wrapping_add(GridIx::new(4, 2), GridIx::new(-3, 2) * 5), GridIx::new(3, 1));
+        assert_eq!(space.wrap(GridIx::new(-1, 22)), GridIx::new(6, 0));
+        let corner = space.wrapping_neighbours4(GridIx::new(0, 0));
+        assert_eq!(corner, [GridIx::new(6, 0), GridIx::new(0, 1), GridIx::new(1, 0), GridIx::new(0, 10)]);
+        let grid = Grid::new(TEXT);
+        assert!(grid.wrapping_neighbours8(GridIx::new(2, 1)).iter().all(|n| n.is_within(&grid)));
+    }
+
+    #[test]
+    fn test_direction() {
+        assert_eq!(Direction::N.cw(), Direction::E);
+        assert_eq!(Direction::N.ccw(), Direction::W);
+        assert_eq!(Direction::W.rotate90(3), Direction::S);
+        assert_eq!(Direction::NE.opposite(), Direction::SW);
+        assert_eq!(Direction::SE.rotate45(-3), Direction::N);
+        assert_eq!(GridIx::from(Direction::SW), GridIx::new(1, -1));
+        assert_eq!(Direction::all().filter(|d| d.is_diagonal()).collect::<Vec<_>>(), Direction::DIAGONAL);
+        for (d, c) in Direction::ORTHOGONAL.into_iter().zip("^>v<".chars()) {
+            assert_eq!(d.cw().ccw(), d);
+            assert_eq!(Direction::try_from(c), Ok(d));
+        }
+        assert_eq!(Direction::try_from('D'), Ok(Direction::S));
+        assert_eq!(Direction::try_from('x'), Err(DirectionFromCharError('x')));
+    }
+
+    #[test]
+    fn test_ray() {
+        let grid = OwnedGrid::new(4, 5, 0);
+        let ray = grid.ray(GridIx::new(0, 0), GridIx::new(1, 2)).collect::<Vec<_>>();
+        assert_eq!(ray, [GridIx::new(1, 2), GridIx::new(2, 4)]);
+        assert_eq!(grid.ray(GridIx::new(3, 4), GridIx::new(0, 1)).next(), None);
+    }
+}
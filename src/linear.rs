@@ -0,0 +1,199 @@
+//! Exact solutions of small systems of linear equations with integer coefficients.
+
+use std::error::Error;
+use std::fmt::Display;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+use crate::math::gcd;
+
+/// Fraction in lowest terms with a positive denominator.
+#[derive(Eq, Hash, PartialEq, Debug, Clone, Copy)]
+pub struct Rational {
+    num: i128,
+    den: i128,
+}
+
+impl Rational {
+    pub const ZERO: Rational = Rational { num: 0, den: 1 };
+    pub const ONE: Rational = Rational { num: 1, den: 1 };
+
+    /// Panics if `den` is zero.
+    pub fn new(num: i128, den: i128) -> Self {
+        assert_ne!(den, 0, "denominator must not be zero");
+        let g = gcd(num, den) * den.signum();
+        Rational { num: num / g, den: den / g }
+    }
+
+    pub fn num(&self) -> i128 {
+        self.num
+    }
+
+    pub fn den(&self) -> i128 {
+        self.den
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.num == 0
+    }
+
+    /// The value if it is a whole number.
+    pub fn to_integer(&self) -> Option<i128> {
+        (self.den == 1).then_some(self.num)
+    }
+}
+
+impl From<i128> for Rational {
+    fn from(n: i128) -> Self {
+        Rational { num: n, den: 1 }
+    }
+}
+
+impl From<i64> for Rational {
+    fn from(n: i64) -> Self {
+        Rational::from(n as i128)
+    }
+}
+
+impl Add for Rational {
+    type Output = Rational;
+
+    fn add(self, rhs: Rational) -> Rational {
+        Rational::new(self.num * rhs.den + rhs.num * self.den, self.den * rhs.den)
+    }
+}
+
+impl Sub for Rational {
+    type Output = Rational;
+
+    fn sub(self, rhs: Rational) -> Rational {
+        self + -rhs
+    }
+}
+
+impl Neg for Rational {
+    type Output = Rational;
+
+    fn neg(self) -> Rational {
+        Rational { num: -self.num, den: self.den }
+    }
+}
+
+impl Mul for Rational {
+    type Output = Rational;
+
+    fn mul(self, rhs: Rational) -> Rational {
+        Rational::new(self.num * rhs.num, self.den * rhs.den)
+    }
+}
+
+impl Div for Rational {
+    type Output = Rational;
+
+    /// Panics when dividing by zero.
+    fn div(self, rhs: Rational) -> Rational {
+        Rational::new(self.num * rhs.den, self.den * rhs.num)
+    }
+}
+
+impl Display for Rational {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.den == 1 {
+            write!(f, "{}", self.num)
+        } else {
+            write!(f, "{}/{}", self.num, self.den)
+        }
+    }
+}
+
+/// Why a system has no unique solution.
+#[derive(Debug, PartialEq)]
+pub enum LinearError {
+    /// The equations contradict each other.
+    Inconsistent,
+    /// There are infinitely many solutions, with `free` variables chosen arbitrarily.
+    Underdetermined { free: usize },
+}
+
+impl Error for LinearError {}
+
+impl Display for LinearError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LinearError::Inconsistent => write!(f, "the system has no solution"),
+            LinearError::Underdetermined { free } =>
+                write!(f, "the system has infinitely many solutions with {free} free variables"),
+        }
+    }
+}
+
+/// Solves `a * x = b` exactly by Gauss-Jordan elimination, where each row of `a` is one equation.
+pub fn solve<const N: usize>(a: [[i64; N]; N], b: [i64; N]) -> Result<[Rational; N], LinearError> {
+    let mut rows = a.iter().zip(b)
+        .map(|(row, b)| row.iter().chain([&b]).map(|&v| Rational::from(v)).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    let mut pivots = Vec::with_capacity(N);
+    for col in 0..N {
+        let r = pivots.len();
+        let Some(p) = (r..N).find(|&p| !rows[p][col].is_zero()) else {
+            continue;
+        };
+        rows.swap(r, p);
+        let pivot = rows[r][col];
+        rows[r].iter_mut().for_each(|v| *v = *v / pivot);
+        let pivot_row = rows[r].clone();
+        for (other, row) in rows.iter_mut().enumerate() {
+            let factor = row[col];
+            if other != r && !factor.is_zero() {
+                row.iter_mut().zip(&pivot_row).for_each(|(v, &p)| *v = *v - factor * p);
+            }
+        }
+        pivots.push(col);
+    }
+    if rows[pivots.len()..].iter().any(|row| !row[N].is_zero()) {
+        return Err(LinearError::Inconsistent);
+    }
+    if pivots.len() < N {
+        return Err(LinearError::Underdetermined { free: N - pivots.len() });
+    }
+    Ok(std::array::from_fn(|i| rows[i][N]))
+}
+
+/// Solves `a * x = b` like [`solve`], but returns `None` unless the solution is all integers.
+pub fn solve_integer<const N: usize>(a: [[i64; N]; N], b: [i64; N]) -> Result<Option<[i64; N]>, LinearError> {
+    let x = solve(a, b)?;
+    let mut result = [0; N];
+    for (r, v) in result.iter_mut().zip(x) {
+        match v.to_integer().map(i64::try_from) {
+            Some(Ok(n)) => *r = n,
+            _ => return Ok(None),
+        }
+    }
+    Ok(Some(result))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rational() {
+        let half = Rational::new(2, -4);
+        assert_eq!((half.num(), half.den()), (-1, 2));
+        assert_eq!(half + Rational::ONE, Rational::new(1, 2));
+        assert_eq!(half * half / Rational::from(2i64), Rational::new(1, 8));
+        assert_eq!((half - half).to_integer(), Some(0));
+        assert_eq!(Rational::new(6, 4).to_string(), "3/2");
+    }
+
+    #[test]
+    fn test_solve() {
+        // Button A: X+94, Y+34; Button B: X+22, Y+67; Prize: X=8400, Y=5400
+        assert_eq!(solve_integer([[94, 22], [34, 67]], [8400, 5400]), Ok(Some([80, 40])));
+        assert_eq!(solve_integer([[26, 67], [66, 21]], [12748, 12176]), Ok(None));
+        let x = solve([[2, 1, -1], [-3, -1, 2], [-2, 1, 2]], [8, -11, -3]).unwrap();
+        assert_eq!(x.map(|v| v.to_integer()), [Some(2), Some(3), Some(-1)]);
+        assert_eq!(solve([[2, 0], [0, 4]], [1, 1]), Ok([Rational::new(1, 2), Rational::new(1, 4)]));
+        assert_eq!(solve([[1, 2], [2, 4]], [3, 7]), Err(LinearError::Inconsistent));
+        assert_eq!(solve([[1, 2], [2, 4]], [3, 6]), Err(LinearError::Underdetermined { free: 1 }));
+        assert_eq!(solve([[0, 0], [0, 0]], [0, 0]), Err(LinearError::Underdetermined { free: 2 }));
+    }
+}
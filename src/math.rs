@@ -0,0 +1,119 @@
+//! Number theory for signed integers, e.g. `i64` or `i128`.
+//!
+//! Modular products must fit into the type, so for moduli above `2^31` use `i128`.
+
+use num_traits::{PrimInt, Signed};
+
+/// Non-negative remainder of `a` divided by `m`.
+pub fn modulo<T: PrimInt + Signed>(a: T, m: T) -> T {
+    let r = a % m;
+    if r < T::zero() { r + m.abs() } else { r }
+}
+
+/// Greatest common divisor, which is never negative.
+pub fn gcd<T: PrimInt + Signed>(a: T, b: T) -> T {
+    let (mut a, mut b) = (a.abs(), b.abs());
+    while !b.is_zero() {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Least common multiple, which is never negative.
+pub fn lcm<T: PrimInt + Signed>(a: T, b: T) -> T {
+    if a.is_zero() || b.is_zero() {
+        return T::zero();
+    }
+    (a / gcd(a, b) * b).abs()
+}
+
+/// Extended Euclid's algorithm, returning `(g, x, y)` such that `a * x + b * y = g = gcd(a, b)`.
+pub fn extended_gcd<T: PrimInt + Signed>(a: T, b: T) -> (T, T, T) {
+    let (mut r0, mut r1) = (a, b);
+    let (mut x0, mut x1) = (T::one(), T::zero());
+    let (mut y0, mut y1) = (T::zero(), T::one());
+    while !r1.is_zero() {
+        let q = r0 / r1;
+        (r0, r1) = (r1, r0 - q * r1);
+        (x0, x1) = (x1, x0 - q * x1);
+        (y0, y1) = (y1, y0 - q * y1);
+    }
+    if r0 < T::zero() { (-r0, -x0, -y0) } else { (r0, x0, y0) }
+}
+
+/// The `x` in `0..m` with `a * x = 1 (mod m)`, if `a` and `m` are coprime.
+pub fn mod_inverse<T: PrimInt + Signed>(a: T, m: T) -> Option<T> {
+    let (g, x, _) = extended_gcd(modulo(a, m), m);
+    g.is_one().then(|| modulo(x, m))
+}
+
+/// `base` to the power of `exp` modulo `m`, by repeated squaring.
+pub fn mod_pow<T: PrimInt + Signed>(base: T, mut exp: u64, m: T) -> T {
+    let mut base = modulo(base, m);
+    let mut result = modulo(T::one(), m);
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = result * base % m;
+        }
+        base = base * base % m;
+        exp >>= 1;
+    }
+    result
+}
+
+/// Solves the system `x = r (mod m)` for each `(r, m)` by the Chinese remainder theorem.
+///
+/// Returns `(x, lcm)` where `x` is the smallest non-negative solution and every other
+/// solution differs by a multiple of `lcm`. The moduli do not have to be coprime,
+/// in which case there may be no solution.
+pub fn crt<T: PrimInt + Signed>(congruences: &[(T, T)]) -> Option<(T, T)> {
+    congruences.iter().try_fold((T::zero(), T::one()), |(x, m), &(r, n)| {
+        let (g, p, _) = extended_gcd(m, n);
+        let diff = r - x;
+        if !(diff % g).is_zero() {
+            return None;
+        }
+        let l = m / g * n;
+        // x + m * k satisfies both when k = diff / g * p (mod n / g)
+        let k = modulo(diff / g % (n / g) * p, n / g);
+        Some((modulo(x + m * k, l), l))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gcd() {
+        assert_eq!(gcd(12i64, -18), 6);
+        assert_eq!(gcd(0i64, 5), 5);
+        assert_eq!(lcm(4i64, -6), 12);
+        assert_eq!(lcm(0i64, 6), 0);
+        let (g, x, y) = extended_gcd(240i64, 46);
+        assert_eq!(g, 2);
+        assert_eq!(240 * x + 46 * y, 2);
+        assert_eq!(modulo(-7i64, 3), 2);
+    }
+
+    #[test]
+    fn test_modular() {
+        assert_eq!(mod_inverse(3i64, 11), Some(4));
+        assert_eq!(mod_inverse(-3i64, 11), Some(7));
+        assert_eq!(mod_inverse(6i64, 9), None);
+        assert_eq!(mod_pow(4i64, 13, 497), 445);
+        assert_eq!(mod_pow(2i64, 0, 1), 0);
+        let big = 1_000_000_007i128;
+        assert_eq!(mod_pow(2, (big - 1) as u64, big), 1);
+    }
+
+    #[test]
+    fn test_crt() {
+        assert_eq!(crt(&[(2i64, 3), (3, 5), (2, 7)]), Some((23, 105)));
+        assert_eq!(crt(&[(1i64, 4), (3, 6)]), Some((9, 12)));
+        assert_eq!(crt(&[(1i64, 4), (2, 6)]), None);
+        assert_eq!(crt::<i64>(&[]), Some((0, 1)));
+        // robots in a 101 x 103 lobby lining up on steps 12 and 88 meet every 10403 steps
+        assert_eq!(crt(&[(12i128, 101), (88, 103)]), Some((6577, 10403)));
+    }
+}
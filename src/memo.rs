@@ -0,0 +1,92 @@
+//! Memoisation of recursive functions, e.g. for counting solutions with dynamic programming.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// Handle passed to a memoised function to make recursive calls through the cache.
+pub struct Context<'a, K, V> {
+    cache: &'a mut HashMap<K, V>,
+    f: &'a dyn Fn(&mut Context<K, V>, K) -> V,
+}
+
+impl<K: Hash + Eq + Clone, V: Clone> Context<'_, K, V> {
+    /// The value for `key`, computed only if it is not cached yet.
+    pub fn get(&mut self, key: K) -> V {
+        if let Some(value) = self.cache.get(&key) {
+            return value.clone();
+        }
+        let f = self.f;
+        let value = f(self, key.clone());
+        self.cache.insert(key, value.clone());
+        value
+    }
+}
+
+/// Function with its results cached by argument, which may be a tuple of several arguments.
+///
+/// The function receives a [`Context`] through which it calls itself recursively,
+/// and the cache is kept between calls to [`Memoised::get`].
+pub struct Memoised<K, V, F> {
+    cache: HashMap<K, V>,
+    f: F,
+}
+
+impl<K, V, F> Memoised<K, V, F>
+where K: Hash + Eq + Clone, V: Clone, F: Fn(&mut Context<K, V>, K) -> V
+{
+    pub fn new(f: F) -> Self {
+        Memoised { cache: HashMap::new(), f }
+    }
+
+    pub fn get(&mut self, key: K) -> V {
+        Context { cache: &mut self.cache, f: &self.f }.get(key)
+    }
+
+    /// Number of cached results.
+    pub fn cached(&self) -> usize {
+        self.cache.len()
+    }
+}
+
+/// Calls a recursive function once with memoisation, see [`Memoised`].
+pub fn memoised<K, V, F>(key: K, f: F) -> V
+where K: Hash + Eq + Clone, V: Clone, F: Fn(&mut Context<K, V>, K) -> V
+{
+    Memoised::new(f).get(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fibonacci(ctx: &mut Context<u64, u64>, n: u64) -> u64 {
+        if n < 2 { n } else { ctx.get(n - 1) + ctx.get(n - 2) }
+    }
+
+    #[test]
+    fn test_memoised() {
+        assert_eq!(memoised(90, fibonacci), 2880067194370816120);
+        let mut fib = Memoised::new(fibonacci);
+        assert_eq!(fib.get(10), 55);
+        assert_eq!(fib.cached(), 11);
+        assert_eq!(fib.get(5), 5);
+        assert_eq!(fib.cached(), 11);
+    }
+
+    #[test]
+    fn test_tuple_key() {
+        // lattice paths in a grid with `walls`
+        let walls = [(1, 1), (2, 3)];
+        let mut paths = Memoised::new(|ctx, (r, c): (u32, u32)| -> u64 {
+            if walls.contains(&(r, c)) {
+                0
+            } else if r == 0 || c == 0 {
+                1
+            } else {
+                ctx.get((r - 1, c)) + ctx.get((r, c - 1))
+            }
+        });
+        assert_eq!(paths.get((2, 2)), 2);
+        assert_eq!(paths.get((3, 3)), 4);
+    }
+}
@@ -0,0 +1,201 @@
+//! Orders given by rules saying which item goes before another.
+
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::error::Error;
+use std::fmt::{Debug, Display};
+use std::hash::Hash;
+
+/// Partial order built from precedence pairs `(before, after)`, e.g. `47|53` page ordering rules.
+///
+/// The rules do not have to be transitive or even acyclic as a whole,
+/// it is enough when the items sorted together are consistent.
+#[derive(Clone, Debug)]
+pub struct PartialOrder<T> {
+    nodes: Vec<T>,
+    index: HashMap<T, usize>,
+    after: Vec<HashSet<usize>>,
+    before: Vec<HashSet<usize>>,
+}
+
+/// Items that must each go before the next one, and the last before the first.
+#[derive(Debug, PartialEq)]
+pub struct CycleError<T> {
+    pub cycle: Vec<T>,
+}
+
+impl<T: Debug> Error for CycleError<T> {}
+
+impl<T: Debug> Display for CycleError<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "the rules order items in a cycle: {:?}", self.cycle)
+    }
+}
+
+impl<T: Hash + Eq + Clone> Default for PartialOrder<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Hash + Eq + Clone> PartialOrder<T> {
+    pub fn new() -> Self {
+        PartialOrder { nodes: Vec::new(), index: HashMap::new(), after: Vec::new(), before: Vec::new() }
+    }
+
+    fn node(&mut self, item: T) -> usize {
+        if let Some(&i) = self.index.get(&item) {
+            return i;
+        }
+        self.index.insert(item.clone(), self.nodes.len());
+        self.nodes.push(item);
+        self.after.push(HashSet::new());
+        self.before.push(HashSet::new());
+        self.nodes.len() - 1
+    }
+
+    /// Adds the rule that `before` goes before `after`.
+    pub fn add(&mut self, before: T, after: T) {
+        let (b, a) = (self.node(before), self.node(after));
+        self.after[b].insert(a);
+        self.before[a].insert(b);
+    }
+
+    /// Whether a rule directly says that `a` goes before `b`.
+    pub fn precedes(&self, a: &T, b: &T) -> bool {
+        match (self.index.get(a), self.index.get(b)) {
+            (Some(a), Some(b)) => self.after[*a].contains(b),
+            _ => false,
+        }
+    }
+
+    /// Compares two items by the rules about them, without following chains of rules.
+    pub fn compare(&self, a: &T, b: &T) -> Option<Ordering> {
+        match (self.precedes(a, b), self.precedes(b, a)) {
+            _ if a == b => Some(Ordering::Equal),
+            (true, false) => Some(Ordering::Less),
+            (false, true) => Some(Ordering::Greater),
+            _ => None,
+        }
+    }
+
+    /// Whether no rule puts a later item of the sequence before an earlier one.
+    pub fn is_sorted(&self, seq: &[T]) -> bool {
+        seq.iter().enumerate().all(|(i, a)| seq[i + 1..].iter().all(|b| !self.precedes(b, a)))
+    }
+
+    /// Sorts the items of the sequence using only the rules between them.
+    ///
+    /// Items not ordered by the rules keep their relative order where possible,
+    /// and repeated items end up next to each other.
+    pub fn sort(&self, seq: &[T]) -> Result<Vec<T>, CycleError<T>> {
+        let mut counts: HashMap<&T, usize> = HashMap::new();
+        let mut distinct = Vec::new();
+        for item in seq {
+            *counts.entry(item).or_insert_with(|| {
+                distinct.push(item);
+                0
+            }) += 1;
+        }
+        let nodes = distinct.iter().map(|&item| self.index.get(item).copied()).collect::<Vec<_>>();
+        Ok(self.topological(&nodes)?
+            .into_iter()
+            .flat_map(|p| std::iter::repeat_n(distinct[p].clone(), counts[distinct[p]]))
+            .collect())
+    }
+
+    /// All items sorted so that every rule holds.
+    pub fn topological_order(&self) -> Result<Vec<T>, CycleError<T>> {
+        let all = (0..self.nodes.len()).map(Some).collect::<Vec<_>>();
+        Ok(self.topological(&all)?.into_iter().map(|i| self.nodes[i].clone()).collect())
+    }
+
+    /// Kahn's algorithm returning positions in `subset`, preferring items listed earlier.
+    ///
+    /// Items without a node have no rules.
+    fn topological(&self, subset: &[Option<usize>]) -> Result<Vec<usize>, CycleError<T>> {
+        let position = subset.iter().enumerate()
+            .filter_map(|(p, i)| i.map(|i| (i, p)))
+            .collect::<HashMap<_, _>>();
+        let mut incoming = subset.iter()
+            .map(|i| i.map_or(0, |i| self.before[i].iter().filter(|b| position.contains_key(b)).count()))
+            .collect::<Vec<_>>();
+        let mut ready = (0..subset.len()).filter(|&p| incoming[p] == 0).map(Reverse).collect::<BinaryHeap<_>>();
+        let mut order = Vec::with_capacity(subset.len());
+        while let Some(Reverse(p)) = ready.pop() {
+            order.push(p);
+            for next in subset[p].iter().flat_map(|&i| &self.after[i]) {
+                if let Some(&q) = position.get(next) {
+                    incoming[q] -= 1;
+                    if incoming[q] == 0 {
+                        ready.push(Reverse(q));
+                    }
+                }
+            }
+        }
+        if order.len() < subset.len() {
+            let left = subset.iter().zip(&incoming).find_map(|(&i, &n)| (n > 0).then_some(i)).flatten().unwrap();
+            return Err(self.cycle_from(left, |i| position.get(&i).is_some_and(|&p| incoming[p] > 0)));
+        }
+        Ok(order)
+    }
+
+    /// Walks back through predecessors among the unsorted nodes until one repeats.
+    fn cycle_from<F: Fn(usize) -> bool>(&self, start: usize, unsorted: F) -> CycleError<T> {
+        let mut path = vec![start];
+        let mut seen = HashMap::from([(start, 0)]);
+        loop {
+            let last = *path.last().unwrap();
+            let previous = *self.before[last].iter().find(|&&b| unsorted(b)).unwrap();
+            if let Some(&p) = seen.get(&previous) {
+                let cycle = path[p..].iter().rev().map(|&i| self.nodes[i].clone()).collect();
+                return CycleError { cycle };
+            }
+            seen.insert(previous, path.len());
+            path.push(previous);
+        }
+    }
+}
+
+impl<T: Hash + Eq + Clone> FromIterator<(T, T)> for PartialOrder<T> {
+    fn from_iter<I: IntoIterator<Item=(T, T)>>(iter: I) -> Self {
+        let mut order = PartialOrder::new();
+        for (before, after) in iter {
+            order.add(before, after);
+        }
+        order
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partial_order() {
+        let rules: PartialOrder<u32> = [(47, 53), (97, 13), (97, 61), (75, 29), (61, 13), (29, 13)].into_iter().collect();
+        assert!(rules.precedes(&47, &53));
+        assert!(!rules.precedes(&53, &47));
+        assert_eq!(rules.compare(&97, &13), Some(Ordering::Less));
+        assert_eq!(rules.compare(&13, &61), Some(Ordering::Greater));
+        assert_eq!(rules.compare(&97, &29), None);
+        assert!(rules.is_sorted(&[75, 97, 61, 13]));
+        assert!(!rules.is_sorted(&[61, 13, 97]));
+        assert_eq!(rules.sort(&[61, 13, 97]), Ok(vec![97, 61, 13]));
+        assert_eq!(rules.sort(&[13, 5, 29, 13]), Ok(vec![5, 29, 13, 13]));
+        let all = rules.topological_order().unwrap();
+        assert!(rules.is_sorted(&all));
+        assert_eq!(all.len(), 7);
+    }
+
+    #[test]
+    fn test_cycle() {
+        let rules: PartialOrder<char> = [('a', 'b'), ('b', 'c'), ('c', 'a'), ('c', 'd'), ('x', 'y')].into_iter().collect();
+        assert_eq!(rules.sort(&['y', 'x']), Ok(vec!['x', 'y']));
+        assert_eq!(rules.sort(&['b', 'c', 'd']), Ok(vec!['b', 'c', 'd']));
+        let error = rules.topological_order().unwrap_err();
+        assert_eq!(error.cycle.len(), 3);
+        assert!(error.cycle.iter().zip(error.cycle.iter().cycle().skip(1)).all(|(a, b)| rules.precedes(a, b)));
+        assert!(rules.sort(&['d', 'a', 'c', 'b']).is_err());
+    }
+}
@@ -0,0 +1,330 @@
+//! Parsers for input shapes that keep coming back, mostly built from nom combinators.
+
+use std::convert::Infallible;
+use std::error::Error;
+use std::fmt::{Debug, Display};
+use std::num::ParseIntError;
+use std::str::FromStr;
+use nom::bytes::complete::tag;
+use nom::character::complete::{char, digit1, line_ending, multispace0, one_of, space0};
+use nom::combinator::{eof, map_res, opt, recognize};
+use nom::multi::{many1, separated_list1};
+use nom::sequence::{pair, preceded, separated_pair, terminated};
+use nom::{IResult, Parser};
+use pathfinding::matrix::MatrixFormatError;
+
+/// Integer with an optional `+` or `-` sign, e.g. `-3` or `+94`.
+pub fn int<T: FromStr>(input: &str) -> IResult<&str, T> {
+    map_res(recognize(pair(opt(one_of("+-")), digit1)), str::parse)(input)
+}
+
+/// Items separated by a comma and optional spaces, e.g. `75,47, 61`.
+pub fn comma_list<'a, O, P>(item: P) -> impl FnMut(&'a str) -> IResult<&'a str, Vec<O>>
+where P: Parser<&'a str, O, nom::error::Error<&'a str>>
+{
+    separated_list1(pair(char(','), space0), item)
+}
+
+/// Pair of integers `x,y`.
+pub fn xy<T: FromStr>(input: &str) -> IResult<&str, (T, T)> {
+    separated_pair(int, char(','), int)(input)
+}
+
+/// Value named by `key`, as in `key=value`.
+pub fn key_value<'a, O, P>(key: &'static str, value: P) -> impl FnMut(&'a str) -> IResult<&'a str, O>
+where P: Parser<&'a str, O, nom::error::Error<&'a str>>
+{
+    preceded(pair(tag(key), char('=')), value)
+}
+
+/// Items on consecutive lines, stopping before a blank line or the end of input.
+pub fn lines<'a, O, P>(item: P) -> impl FnMut(&'a str) -> IResult<&'a str, Vec<O>>
+where P: Parser<&'a str, O, nom::error::Error<&'a str>>
+{
+    separated_list1(line_ending, item)
+}
+
+/// One or more blank lines between blocks, which may contain spaces.
+pub fn blank_lines(input: &str) -> IResult<&str, ()> {
+    let (input, _) = pair(line_ending, many1(terminated(space0, line_ending)))(input)?;
+    Ok((input, ()))
+}
+
+/// Blocks separated by blank lines, each parsed by `block`.
+pub fn blocks<'a, O, P>(block: P) -> impl FnMut(&'a str) -> IResult<&'a str, Vec<O>>
+where P: Parser<&'a str, O, nom::error::Error<&'a str>>
+{
+    separated_list1(blank_lines, block)
+}
+
+/// Failure to parse the whole input, pointing at where the parser stopped.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    /// 1-based line where the parser stopped.
+    pub line: usize,
+    /// 1-based column (in characters) where the parser stopped.
+    pub col: usize,
+    /// The rest of that line, which could not be parsed.
+    pub remainder: String,
+}
+
+impl ParseError {
+    fn at(input: &str, rest: &str) -> Self {
+        let parsed = &input[..input.len() - rest.len()];
+        let line_start = parsed.rfind('\n').map_or(0, |i| i + 1);
+        ParseError {
+            line: parsed.matches('\n').count() + 1,
+            col: parsed[line_start..].chars().count() + 1,
+            remainder: rest.lines().next().unwrap_or_default().to_string(),
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "could not parse line {} from column {}: {:?}", self.line, self.col, self.remainder)
+    }
+}
+
+/// Runs `parser` on the whole input, which may only be followed by trailing whitespace.
+pub fn parse_all<'a, O, P>(mut parser: P, input: &'a str) -> Result<O, ParseError>
+where P: Parser<&'a str, O, nom::error::Error<&'a str>>
+{
+    match parser.parse(input) {
+        Ok((rest, output)) => {
+            let (rest, _) = multispace0::<_, ()>(rest).unwrap_or((rest, ""));
+            match eof::<_, ()>(rest) {
+                Ok(_) => Ok(output),
+                Err(_) => Err(ParseError::at(input, rest)),
+            }
+        }
+        Err(nom::Err::Error(e) | nom::Err::Failure(e)) => Err(ParseError::at(input, e.input)),
+        Err(nom::Err::Incomplete(_)) => Err(ParseError::at(input, "")),
+    }
+}
+
+/// Every integer in the text in order, ignoring anything between them.
+///
+/// A `-` right before digits is read as a sign, unless `T` is unsigned, so `3,-3` gives `3` and `-3`.
+/// Numbers that do not fit into `T` are skipped.
+pub fn ints<T: FromStr>(text: &str) -> impl Iterator<Item=T> + '_ {
+    let bytes = text.as_bytes();
+    let mut i = 0;
+    std::iter::from_fn(move || loop {
+        while i < bytes.len() && !bytes[i].is_ascii_digit() {
+            i += 1;
+        }
+        if i == bytes.len() {
+            return None;
+        }
+        let start = i;
+        while i < bytes.len() && bytes[i].is_ascii_digit() {
+            i += 1;
+        }
+        if start > 0 && bytes[start - 1] == b'-' {
+            if let Ok(n) = text[start - 1..i].parse() {
+                return Some(n);
+            }
+        }
+        if let Ok(n) = text[start..i].parse() {
+            return Some(n);
+        }
+    })
+}
+
+/// Exactly `N` integers from the text, e.g. `[px, py, vx, vy]` from `p=0,4 v=3,-3`.
+pub fn int_array<T: FromStr, const N: usize>(text: &str) -> Option<[T; N]> {
+    ints(text).collect::<Vec<_>>().try_into().ok()
+}
+
+/// The integers of each line.
+pub fn ints_per_line<T: FromStr>(text: &str) -> impl Iterator<Item=Vec<T>> + '_ {
+    text.lines().map(|line| ints(line).collect())
+}
+
+/// The integers of each section, see [`sections`].
+pub fn ints_per_block<T: FromStr>(text: &str) -> Vec<Vec<T>> {
+    sections(text).map(|section| ints(section).collect()).collect()
+}
+
+fn split_sections(text: &str) -> Vec<(usize, &str)> {
+    let mut found = Vec::new();
+    let mut start: Option<(usize, usize)> = None;
+    let mut offset = 0;
+    for (line_no, line) in text.split_inclusive('\n').enumerate() {
+        if line.trim().is_empty() {
+            if let Some((first_line, begin)) = start.take() {
+                found.push((first_line, text[begin..offset].trim_end()));
+            }
+        } else if start.is_none() {
+            start = Some((line_no + 1, offset));
+        }
+        offset += line.len();
+    }
+    if let Some((first_line, begin)) = start {
+        found.push((first_line, text[begin..].trim_end()));
+    }
+    found
+}
+
+/// Sections of the input separated by one or more blank lines.
+///
+/// Lines with only whitespace count as blank, and each section has trailing whitespace removed.
+pub fn sections(text: &str) -> impl Iterator<Item=&str> {
+    split_sections(text).into_iter().map(|(_, section)| section)
+}
+
+/// Input made of sections separated by blank lines, parsed one section at a time.
+pub struct Sections<'a> {
+    sections: Vec<(usize, &'a str)>,
+    parsed: usize,
+}
+
+impl<'a> Sections<'a> {
+    pub fn new(text: &'a str) -> Self {
+        Sections { sections: split_sections(text), parsed: 0 }
+    }
+
+    /// Parses the next section, using `name` to describe it in errors.
+    ///
+    /// Lines in the parser's error are moved to point at their line in the whole input.
+    pub fn parse<T, E, F>(&mut self, name: &'static str, parser: F) -> Result<T, SectionError>
+    where E: ShiftLines, F: FnOnce(&'a str) -> Result<T, E>
+    {
+        let &(line, text) = self.sections.get(self.parsed)
+            .ok_or(SectionError::Missing { name, index: self.parsed + 1 })?;
+        self.parsed += 1;
+        parser(text).map_err(|e| SectionError::Invalid { name, line, error: e.shift_lines(line - 1).to_string() })
+    }
+
+    /// Checks that all sections have been parsed.
+    pub fn finish(self) -> Result<(), SectionError> {
+        match self.sections.get(self.parsed) {
+            Some(&(line, _)) => Err(SectionError::Unexpected { line, count: self.sections.len() - self.parsed }),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Error of a section parser, which may point at a line counted from the start of the section.
+pub trait ShiftLines: Display {
+    /// Moves the line numbers in the error `by` lines down, errors without lines stay the same.
+    fn shift_lines(self, _by: usize) -> Self where Self: Sized {
+        self
+    }
+}
+
+impl ShiftLines for ParseError {
+    fn shift_lines(self, by: usize) -> Self {
+        ParseError { line: self.line + by, ..self }
+    }
+}
+
+impl ShiftLines for Infallible {}
+
+impl ShiftLines for ParseIntError {}
+
+impl ShiftLines for MatrixFormatError {}
+
+impl<E: Debug> ShiftLines for nom::Err<E> {}
+
+/// Failure to parse input made of [`Sections`].
+#[derive(Debug, PartialEq)]
+pub enum SectionError {
+    Missing { name: &'static str, index: usize },
+    Invalid { name: &'static str, line: usize, error: String },
+    Unexpected { line: usize, count: usize },
+}
+
+impl Error for SectionError {}
+
+impl Display for SectionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SectionError::Missing { name, index } =>
+                write!(f, "missing section {index} with {name}"),
+            SectionError::Invalid { name, line, error } =>
+                write!(f, "could not parse {name} in the section starting on line {line}: {error}"),
+            SectionError::Unexpected { line, count } =>
+                write!(f, "{count} unexpected sections starting on line {line}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_numbers() {
+        assert_eq!(int::<i64>("-12 rest"), Ok((" rest", -12)));
+        assert_eq!(int::<i32>("+94"), Ok(("", 94)));
+        assert!(int::<u32>("-1").is_err());
+        assert_eq!(xy::<i64>("3,-3 v"), Ok((" v", (3, -3))));
+        assert_eq!(comma_list(int::<u32>)("75,47, 61\n"), Ok(("\n", vec![75, 47, 61])));
+        assert_eq!(key_value("p", xy::<i64>)("p=0,4"), Ok(("", (0, 4))));
+    }
+
+    #[test]
+    fn test_blocks() {
+        let input = "1,2\n3,4\n\n5,6\r\n  \r\n\n7,8\n";
+        let parsed = parse_all(blocks(lines(xy::<u8>)), input).unwrap();
+        assert_eq!(parsed, [vec![(1, 2), (3, 4)], vec![(5, 6)], vec![(7, 8)]]);
+        assert_eq!(parse_all(lines(int::<u8>), "1\n2\n\n\n").unwrap(), [1, 2]);
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let error = parse_all(lines(xy::<i64>), "1,2\n3,4\n5;6\n").unwrap_err();
+        assert_eq!(error, ParseError { line: 3, col: 1, remainder: "5;6".to_string() });
+        let error = parse_all(lines(key_value("x", int::<i64>)), "x=1\nx=2y\n").unwrap_err();
+        assert_eq!((error.line, error.col, error.remainder.as_str()), (2, 4, "y"));
+        assert_eq!(error.to_string(), "could not parse line 2 from column 4: \"y\"");
+    }
+
+    #[test]
+    fn test_ints() {
+        assert_eq!(ints::<i64>("p=0,4 v=3,-3").collect::<Vec<_>>(), [0, 4, 3, -3]);
+        assert_eq!(ints::<u32>("2-4,6-8").collect::<Vec<_>>(), [2, 4, 6, 8]);
+        assert_eq!(ints::<i32>("no numbers").next(), None);
+        assert_eq!(ints::<i64>("id 123456789012345678901234 is 7").collect::<Vec<_>>(), [7]);
+        assert_eq!(ints::<u8>("300 -1 255").collect::<Vec<_>>(), [1, 255]);
+        assert_eq!(int_array::<i64, 2>("Prize: X=8400, Y=5400"), Some([8400, 5400]));
+        assert_eq!(int_array::<i64, 3>("X+94, Y+34"), None);
+        let per_line = ints_per_line::<u8>("1 2\n\n3").collect::<Vec<_>>();
+        assert_eq!(per_line, [vec![1, 2], vec![], vec![3]]);
+        let per_block = ints_per_block::<u8>("A: 1\nB: 2\r\n \r\n\nC: 3\n");
+        assert_eq!(per_block, [vec![1, 2], vec![3]]);
+    }
+
+    #[test]
+    fn test_sections() {
+        let input = "1|2\r\n3|4  \r\n\r\n \n1,2\n\n#.\n.#\n\n";
+        assert_eq!(sections(input).collect::<Vec<_>>(), ["1|2\r\n3|4", "1,2", "#.\n.#"]);
+
+        let mut parts = Sections::new(input);
+        let rules = parts.parse("rules", |s| parse_all(lines(separated_pair(int::<u8>, char('|'), int)), s));
+        assert_eq!(rules, Ok(vec![(1, 2), (3, 4)]));
+        let updates = parts.parse("updates", |s| parse_all(lines(comma_list(int::<u8>)), s));
+        assert_eq!(updates, Ok(vec![vec![1, 2]]));
+        assert_eq!(parts.finish(), Err(SectionError::Unexpected { line: 7, count: 1 }));
+
+        let mut parts = Sections::new("1,2\n\nx");
+        assert!(parts.parse("pairs", |s| parse_all(xy::<u8>, s)).is_ok());
+        let error = parts.parse("numbers", |s| parse_all(int::<u8>, s)).unwrap_err();
+        assert_eq!(error.to_string(), "could not parse numbers in the section starting on line 3: could not parse line 3 from column 1: \"x\"");
+        assert_eq!(parts.parse("more", Ok::<_, ParseError>), Err(SectionError::Missing { name: "more", index: 3 }));
+
+        let mut parts = Sections::new("1\n\n2\n3\nx4\n");
+        assert!(parts.parse("first", |s| parse_all(int::<u8>, s)).is_ok());
+        let error = parts.parse("rest", |s| parse_all(lines(int::<u8>), s)).unwrap_err();
+        assert!(error.to_string().ends_with("could not parse line 5 from column 1: \"x4\""), "{error}");
+
+        let owned = String::from("12\n\n3");
+        let mut parts = Sections::new(&owned);
+        assert_eq!(parts.parse("digits", digit1::<&str, nom::error::Error<&str>>), Ok(("", "12")));
+        assert_eq!(parts.parse("number", str::parse::<u8>), Ok(3));
+    }
+}
@@ -0,0 +1,307 @@
+//! Counting and enumerating paths in graphs given by a successor function.
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::hash::Hash;
+
+/// Counts the distinct paths from a node to any goal in a directed acyclic graph.
+///
+/// The count of each visited node is cached, so counting from many starts
+/// that share parts of the graph does not repeat the work.
+pub struct PathCounter<N, FN, FS> {
+    successors: FN,
+    success: FS,
+    cache: HashMap<N, usize>,
+}
+
+impl<N, FN, IN, FS> PathCounter<N, FN, FS>
+where
+    N: Eq + Hash + Clone,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item=N>,
+    FS: FnMut(&N) -> bool,
+{
+    pub fn new(successors: FN, success: FS) -> Self {
+        PathCounter { successors, success, cache: HashMap::new() }
+    }
+
+    /// Number of distinct paths from `node` to a goal. Paths end at the first goal they reach.
+    pub fn count(&mut self, node: &N) -> usize {
+        if let Some(&count) = self.cache.get(node) {
+            return count;
+        }
+        let count = if (self.success)(node) {
+            1
+        } else {
+            let next = (self.successors)(node).into_iter().collect::<Vec<_>>();
+            next.iter().map(|n| self.count(n)).sum()
+        };
+        self.cache.insert(node.clone(), count);
+        count
+    }
+}
+
+/// Number of distinct paths from `start` to a goal in a directed acyclic graph.
+pub fn count_paths<N, FN, IN, FS>(start: N, successors: FN, success: FS) -> usize
+where
+    N: Eq + Hash + Clone,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item=N>,
+    FS: FnMut(&N) -> bool,
+{
+    PathCounter::new(successors, success).count(&start)
+}
+
+/// Dijkstra's search from `start` keeping every predecessor on a cheapest path to each node.
+///
+/// Returns the cheapest cost of each explored node and its predecessors. Stops exploring
+/// once all nodes cheaper than the cheapest goal are known, so pass `|_| false` to explore everything.
+///
+/// Zero-cost edges between equally distant nodes can make the predecessors cyclic.
+pub fn dijkstra_predecessors<N, FN, IN, FS>(start: N, mut successors: FN, mut success: FS)
+    -> (HashMap<N, usize>, HashMap<N, Vec<N>>)
+where
+    N: Eq + Hash + Clone + Ord,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item=(N, usize)>,
+    FS: FnMut(&N) -> bool,
+{
+    let mut distances = HashMap::from([(start.clone(), 0)]);
+    let mut predecessors: HashMap<N, Vec<N>> = HashMap::new();
+    let mut heap = BinaryHeap::from([Reverse((0, start))]);
+    let mut best = None;
+    while let Some(Reverse((cost, node))) = heap.pop() {
+        if best.is_some_and(|b| cost > b) {
+            break;
+        }
+        if cost > distances[&node] {
+            continue;
+        }
+        if success(&node) {
+            best = Some(cost);
+        }
+        for (next, step) in successors(&node) {
+            let next_cost = cost + step;
+            match distances.get(&next) {
+                Some(&known) if known < next_cost => {}
+                Some(&known) if known == next_cost => predecessors.entry(next).or_default().push(node.clone()),
+                _ => {
+                    distances.insert(next.clone(), next_cost);
+                    predecessors.insert(next.clone(), vec![node.clone()]);
+                    heap.push(Reverse((next_cost, next)));
+                }
+            }
+        }
+    }
+    (distances, predecessors)
+}
+
+/// All cheapest paths from a start to the cheapest goals, see [`all_shortest_paths`].
+#[derive(Clone, Debug)]
+pub struct ShortestPaths<N> {
+    start: N,
+    cost: usize,
+    goals: Vec<N>,
+    predecessors: HashMap<N, Vec<N>>,
+}
+
+/// Finds all cheapest paths from `start` to nodes satisfying `success`, or `None` if no goal is reachable.
+pub fn all_shortest_paths<N, FN, IN, FS>(start: N, successors: FN, mut success: FS) -> Option<ShortestPaths<N>>
+where
+    N: Eq + Hash + Clone + Ord,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item=(N, usize)>,
+    FS: FnMut(&N) -> bool,
+{
+    let (distances, predecessors) = dijkstra_predecessors(start.clone(), successors, &mut success);
+    let cost = distances.iter().filter(|(n, _)| success(n)).map(|(_, &c)| c).min()?;
+    let mut goals = distances.into_iter()
+        .filter(|(n, c)| *c == cost && success(n))
+        .map(|(n, _)| n)
+        .collect::<Vec<_>>();
+    goals.sort();
+    Some(ShortestPaths { start, cost, goals, predecessors })
+}
+
+impl<N: Eq + Hash + Clone> ShortestPaths<N> {
+    /// Cost of each of the paths.
+    pub fn cost(&self) -> usize {
+        self.cost
+    }
+
+    /// The reachable goals with the cheapest cost.
+    pub fn goals(&self) -> &[N] {
+        &self.goals
+    }
+
+    fn predecessors(&self, node: &N) -> &[N] {
+        if *node == self.start {
+            return &[];
+        }
+        self.predecessors.get(node).map_or(&[], |p| p.as_slice())
+    }
+
+    /// Every node lying on at least one of the paths.
+    pub fn nodes(&self) -> HashSet<N> {
+        let mut seen: HashSet<N> = self.goals.iter().cloned().collect();
+        let mut stack = self.goals.clone();
+        while let Some(node) = stack.pop() {
+            for previous in self.predecessors(&node) {
+                if seen.insert(previous.clone()) {
+                    stack.push(previous.clone());
+                }
+            }
+        }
+        seen
+    }
+
+    /// Number of distinct paths, counted without enumerating them.
+    ///
+    /// Paths never visit a node twice, so zero-cost cycles do not add infinitely many paths.
+    pub fn count(&self) -> usize {
+        let mut cache = HashMap::new();
+        let mut on_path = HashMap::new();
+        self.goals.iter().map(|g| self.count_back(g, &mut on_path, &mut cache).0).sum()
+    }
+
+    /// Paths from the start to `node` avoiding the nodes `on_path`, which map to their depth.
+    ///
+    /// Also returns the smallest depth of a node on the path that was avoided. If that is
+    /// below `node`, it is not on a cycle, so the count does not depend on the path and can be cached.
+    fn count_back(&self, node: &N, on_path: &mut HashMap<N, usize>, cache: &mut HashMap<N, usize>) -> (usize, usize) {
+        if *node == self.start {
+            return (1, usize::MAX);
+        }
+        let depth = on_path.len();
+        on_path.insert(node.clone(), depth);
+        let mut count = 0;
+        let mut avoided = usize::MAX;
+        for previous in self.predecessors(node) {
+            if let Some(&d) = on_path.get(previous) {
+                avoided = avoided.min(d);
+            } else if let Some(&c) = cache.get(previous) {
+                count += c;
+            } else {
+                let (c, a) = self.count_back(previous, on_path, cache);
+                count += c;
+                avoided = avoided.min(a);
+            }
+        }
+        on_path.remove(node);
+        if avoided > depth {
+            cache.insert(node.clone(), count);
+        }
+        (count, avoided)
+    }
+
+    /// Lazily enumerates the paths from start to goal, one at a time.
+    ///
+    /// Like [`ShortestPaths::count`], paths never visit a node twice.
+    pub fn paths(&self) -> impl Iterator<Item=Vec<N>> + '_ {
+        // partial paths walked back from a goal
+        let mut stack = self.goals.iter().map(|g| vec![g.clone()]).collect::<Vec<_>>();
+        std::iter::from_fn(move || {
+            while let Some(partial) = stack.pop() {
+                let head = partial.last().unwrap();
+                if *head == self.start {
+                    return Some(partial.into_iter().rev().collect());
+                }
+                for previous in self.predecessors(head).iter().filter(|p| !partial.contains(p)) {
+                    let mut longer = partial.clone();
+                    longer.push(previous.clone());
+                    stack.push(longer);
+                }
+            }
+            None
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 0 -> 1 -> 3 -> 4
+    //  \-> 2 -/ \-> 5
+    fn dag(n: &u32) -> Vec<u32> {
+        match n {
+            0 => vec![1, 2],
+            1 | 2 => vec![3],
+            3 => vec![4, 5],
+            _ => vec![],
+        }
+    }
+
+    #[test]
+    fn test_count_paths() {
+        assert_eq!(count_paths(0, dag, |&n| n >= 4), 4);
+        assert_eq!(count_paths(0, dag, |&n| n == 5), 2);
+        assert_eq!(count_paths(4, dag, |&n| n == 5), 0);
+        let mut counter = PathCounter::new(dag, |&n| n == 4);
+        assert_eq!(counter.count(&1), 1);
+        assert_eq!(counter.count(&0), 2);
+    }
+
+    // a square with a costly diagonal, all routes from corner 0 to corner 3 cost 2
+    fn square(n: &u32) -> Vec<(u32, usize)> {
+        match n {
+            0 => vec![(1, 1), (2, 1), (3, 2), (4, 5)],
+            1 | 2 => vec![(3, 1)],
+            4 => vec![(3, 0)],
+            _ => vec![],
+        }
+    }
+
+    #[test]
+    fn test_all_shortest_paths() {
+        let paths = all_shortest_paths(0, square, |&n| n == 3).unwrap();
+        assert_eq!(paths.cost(), 2);
+        assert_eq!(paths.goals(), [3]);
+        assert_eq!(paths.count(), 3);
+        let mut all = paths.paths().collect::<Vec<_>>();
+        all.sort();
+        assert_eq!(all, [vec![0, 1, 3], vec![0, 2, 3], vec![0, 3]]);
+        assert_eq!(paths.nodes(), HashSet::from([0, 1, 2, 3]));
+        assert!(all_shortest_paths(3, square, |&n| n == 0).is_none());
+        let (distances, _) = dijkstra_predecessors(0, square, |_| false);
+        assert_eq!(distances[&4], 5);
+    }
+
+    #[test]
+    fn test_zero_cost_cycle() {
+        let zero = |n: &u32| match n {
+            0 => vec![(1, 0), (2, 0)],
+            1 => vec![(2, 0), (3, 1)],
+            2 => vec![(1, 0), (4, 1)],
+            _ => vec![],
+        };
+        let paths = all_shortest_paths(0, zero, |&n| n == 3).unwrap();
+        assert_eq!(paths.cost(), 1);
+        assert_eq!(paths.count(), 2);
+        let mut all = paths.paths().collect::<Vec<_>>();
+        all.sort();
+        assert_eq!(all, [vec![0, 1, 3], vec![0, 2, 1, 3]]);
+        let paths = all_shortest_paths(0, zero, |&n| n == 2).unwrap();
+        assert_eq!(paths.count(), 2);
+        assert_eq!(paths.paths().count(), 2);
+        assert_eq!(paths.nodes(), HashSet::from([0, 1, 2]));
+        let paths = all_shortest_paths(0, zero, |&n| n >= 3).unwrap();
+        assert_eq!(paths.count(), 4);
+        assert_eq!(paths.paths().count(), 4);
+    }
+
+    #[test]
+    fn test_zero_cost_edge_after_goal() {
+        let graph = |n: &u32| match n {
+            0 => vec![(1, 1), (2, 1)],
+            2 => vec![(1, 0)],
+            _ => vec![],
+        };
+        let paths = all_shortest_paths(0, graph, |&n| n == 1).unwrap();
+        assert_eq!(paths.cost(), 1);
+        assert_eq!(paths.count(), 2);
+        let mut all = paths.paths().collect::<Vec<_>>();
+        all.sort();
+        assert_eq!(all, [vec![0, 1], vec![0, 2, 1]]);
+    }
+}
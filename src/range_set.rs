@@ -0,0 +1,194 @@
+//! Sets of integers stored as disjoint ranges, e.g. free space on a disk.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::ops::Range;
+
+/// Set of `usize` values stored as sorted, disjoint and non-adjacent ranges.
+///
+/// Ranges are also indexed by length, so finding the first range big enough
+/// for an allocation does not have to look at every range.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RangeSet {
+    ranges: BTreeMap<usize, usize>,
+    by_len: BTreeMap<usize, BTreeSet<usize>>,
+}
+
+impl RangeSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn add(&mut self, range: Range<usize>) {
+        self.ranges.insert(range.start, range.end);
+        self.by_len.entry(range.len()).or_default().insert(range.start);
+    }
+
+    fn take(&mut self, start: usize) -> Range<usize> {
+        let end = self.ranges.remove(&start).expect("range starts at start");
+        let starts = self.by_len.get_mut(&(end - start)).unwrap();
+        starts.remove(&start);
+        if starts.is_empty() {
+            self.by_len.remove(&(end - start));
+        }
+        start..end
+    }
+
+    /// Starts of the stored ranges touching or overlapping the range.
+    fn touching(&self, range: &Range<usize>) -> Vec<usize> {
+        let before = self.ranges.range(..range.start).next_back()
+            .filter(|&(_, &end)| end >= range.start)
+            .map(|(&start, _)| start);
+        before.into_iter()
+            .chain(self.ranges.range(range.start..=range.end).map(|(&start, _)| start))
+            .collect()
+    }
+
+    /// Adds all values in the range, merging it with overlapping or adjacent ranges.
+    pub fn insert(&mut self, range: Range<usize>) {
+        if range.is_empty() {
+            return;
+        }
+        let mut merged = range.clone();
+        for start in self.touching(&range) {
+            let other = self.take(start);
+            merged = merged.start.min(other.start)..merged.end.max(other.end);
+        }
+        self.add(merged);
+    }
+
+    /// Removes all values in the range, splitting ranges that stick out on either side.
+    pub fn remove(&mut self, range: Range<usize>) {
+        if range.is_empty() {
+            return;
+        }
+        for start in self.touching(&range) {
+            let other = self.take(start);
+            for rest in [other.start..range.start.min(other.end), range.end.max(other.start)..other.end] {
+                if !rest.is_empty() {
+                    self.add(rest);
+                }
+            }
+        }
+    }
+
+    /// Adds all values of the other set.
+    pub fn union(&mut self, other: &RangeSet) {
+        other.iter().for_each(|range| self.insert(range));
+    }
+
+    pub fn contains(&self, value: usize) -> bool {
+        self.ranges.range(..=value).next_back().is_some_and(|(_, &end)| value < end)
+    }
+
+    /// Whether any value in the range is in the set.
+    pub fn overlaps(&self, range: &Range<usize>) -> bool {
+        self.touching(range).into_iter().any(|start| {
+            let end = self.ranges[&start];
+            start < range.end && range.start < end
+        })
+    }
+
+    /// The first range with at least `size` values, if its first `size` values are all before `before`.
+    ///
+    /// With free disk space in the set, this is where to move a file of `size` blocks found at `before`.
+    pub fn first_fit(&self, size: usize, before: usize) -> Option<Range<usize>> {
+        let start = self.by_len.range(size.max(1)..)
+            .filter_map(|(_, starts)| starts.first())
+            .min()
+            .filter(|&&start| start + size <= before)?;
+        Some(*start..self.ranges[start])
+    }
+
+    /// The ranges missing from the set within `bounds`.
+    pub fn gaps(&self, bounds: Range<usize>) -> impl Iterator<Item=Range<usize>> {
+        let mut covered = self.clone();
+        covered.remove(0..bounds.start);
+        covered.remove(bounds.end..usize::MAX);
+        let mut pos = bounds.start;
+        let mut gaps = Vec::new();
+        for range in covered.iter() {
+            if pos < range.start {
+                gaps.push(pos..range.start);
+            }
+            pos = range.end;
+        }
+        if pos < bounds.end {
+            gaps.push(pos..bounds.end);
+        }
+        gaps.into_iter()
+    }
+
+    /// The ranges in increasing order.
+    pub fn iter(&self) -> impl Iterator<Item=Range<usize>> + '_ {
+        self.ranges.iter().map(|(&start, &end)| start..end)
+    }
+
+    /// Number of disjoint ranges.
+    pub fn len(&self) -> usize {
+        self.ranges.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Number of values in the set.
+    pub fn count(&self) -> usize {
+        self.iter().map(|r| r.len()).sum()
+    }
+}
+
+impl Extend<Range<usize>> for RangeSet {
+    fn extend<I: IntoIterator<Item=Range<usize>>>(&mut self, iter: I) {
+        iter.into_iter().for_each(|range| self.insert(range));
+    }
+}
+
+impl FromIterator<Range<usize>> for RangeSet {
+    fn from_iter<I: IntoIterator<Item=Range<usize>>>(iter: I) -> Self {
+        let mut set = RangeSet::new();
+        set.extend(iter);
+        set
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_remove() {
+        let mut set: RangeSet = [5..8, 0..2, 2..3, 10..12, 7..9].into_iter().collect();
+        assert_eq!(set.iter().collect::<Vec<_>>(), [0..3, 5..9, 10..12]);
+        assert_eq!((set.len(), set.count()), (3, 9));
+        assert!(set.contains(8) && !set.contains(9) && !set.contains(3));
+        assert!(set.overlaps(&(8..10)) && !set.overlaps(&(9..10)));
+        set.remove(1..6);
+        assert_eq!(set.iter().collect::<Vec<_>>(), [0..1, 6..9, 10..12]);
+        set.remove(7..8);
+        set.remove(0..100);
+        assert!(set.is_empty());
+        set.union(&[1..4, 4..5].into_iter().collect());
+        assert_eq!(set.iter().next(), Some(1..5));
+        set.insert(3..3);
+        assert_eq!(set.len(), 1);
+    }
+
+    #[test]
+    fn test_first_fit() {
+        // free space of the disk 00...111...2...333.44.5555.6666.777.888899
+        let free: RangeSet = [2..5, 8..11, 12..15, 18..19, 21..22, 26..27, 31..32, 35..36].into_iter().collect();
+        assert_eq!(free.first_fit(2, 40), Some(2..5));
+        assert_eq!(free.first_fit(1, 40), Some(2..5));
+        assert_eq!(free.first_fit(4, 40), None);
+        assert_eq!(free.first_fit(3, 2), None);
+        let mut free = free;
+        free.remove(2..5);
+        assert_eq!(free.first_fit(3, 40), Some(8..11));
+        assert_eq!(free.gaps(0..14).collect::<Vec<_>>(), [0..8, 11..12]);
+        let mut free = RangeSet::new();
+        free.insert(0..10);
+        assert_eq!(free.first_fit(5, 2), None);
+        assert_eq!(free.first_fit(5, 5), Some(0..10));
+    }
+}